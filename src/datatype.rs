@@ -1,6 +1,7 @@
 pub mod automata_rules;
 pub mod buffers;
 pub mod color_blend_functions;
+pub mod color_ramps;
 pub mod colors;
 pub mod complex;
 pub mod constraint_resolvers;
@@ -10,5 +11,8 @@ pub mod distance_functions;
 pub mod iterative_results;
 pub mod matrices;
 pub mod noisefunctions;
+pub mod oscillators;
 pub mod point_sets;
 pub mod points;
+pub mod reaction_diffusion;
+pub mod spatial_hash;
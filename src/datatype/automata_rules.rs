@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ElementaryAutomataRule {
     pub pattern: [Boolean; 8],
 }
@@ -47,6 +47,87 @@ impl ElementaryAutomataRule {
             ],
         }
     }
+
+    /// Inverse of from_wolfram_code: bit i of the code is the output for neighbourhood index i
+    pub fn to_wolfram_code(&self) -> u8 {
+        self.pattern
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.into_inner())
+            .fold(0, |code, (i, _)| code | (1 << i))
+    }
+
+    /// The rule's number in Wolfram's numbering, e.g. 30 or 110
+    pub fn rule_number(&self) -> u8 {
+        self.to_wolfram_code()
+    }
+
+    /// Computes the next generation of a row. Without wrapping, cells beyond the ends are dead.
+    pub fn step(&self, row: &Array1<Boolean>, wrap: bool) -> Array1<Boolean> {
+        let width = row.len();
+        let cell = |x: isize| {
+            if wrap {
+                row[x.rem_euclid(width as isize) as usize]
+            } else if x < 0 || x >= width as isize {
+                Boolean::new(false)
+            } else {
+                row[x as usize]
+            }
+        };
+
+        Array1::from_shape_fn(width, |x| {
+            let x = x as isize;
+            self.get_value_from_booleans(cell(x - 1), cell(x), cell(x + 1))
+        })
+    }
+
+    /// Renders a Wolfram-style spacetime diagram, with the initial row at the top and each
+    /// following row the next generation. Live cells are white and dead cells are black.
+    #[track_caller]
+    pub fn spacetime(
+        &self,
+        width: usize,
+        generations: usize,
+        initial: &Array1<Boolean>,
+        wrap: bool,
+    ) -> Buffer<BitColor> {
+        assert_eq!(
+            initial.len(),
+            width,
+            "Initial row length does not match spacetime width"
+        );
+
+        let mut array = Array2::from_elem((generations, width), BitColor::Black);
+        let mut row = initial.clone();
+
+        for mut output_row in array.rows_mut() {
+            for (output, cell) in output_row.iter_mut().zip(row.iter()) {
+                *output = if cell.into_inner() {
+                    BitColor::White
+                } else {
+                    BitColor::Black
+                };
+            }
+
+            row = self.step(&row, wrap);
+        }
+
+        Buffer::new(array)
+    }
+}
+
+impl Crossover for ElementaryAutomataRule {
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        let mut pattern = a.pattern;
+
+        for (bit, other) in pattern.iter_mut().zip(b.pattern.iter()) {
+            if rng.gen::<bool>() {
+                *bit = *other;
+            }
+        }
+
+        Self { pattern }
+    }
 }
 
 impl<'a> Generatable<'a> for ElementaryAutomataRule {
@@ -72,12 +153,23 @@ impl<'a> Generatable<'a> for ElementaryAutomataRule {
 impl<'a> Mutatable<'a> for ElementaryAutomataRule {
     type MutArg = ProtoMutArg<'a>;
 
+    /// Mostly steps to an adjacent rule, by flipping one output or moving the code up or down one
     fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: Self::MutArg) {
-        if thread_rng().gen::<bool>() {
-            *self = Self::generate_rng(rng, arg.into());
-        } else {
-            let index = thread_rng().gen::<usize>() % 8;
-            self.pattern[index] = Boolean::new(!self.pattern[index].into_inner());
+        match rng.gen_range(0..4) {
+            0 => *self = Self::generate_rng(rng, arg.into()),
+            1 | 2 => {
+                let index = rng.gen_range(0..8);
+                self.pattern[index] = Boolean::new(!self.pattern[index].into_inner());
+            }
+            3 => {
+                let code = self.to_wolfram_code();
+                *self = Self::from_wolfram_code(if rng.gen() {
+                    code.wrapping_add(1)
+                } else {
+                    code.wrapping_sub(1)
+                });
+            }
+            _ => unreachable!(),
         }
     }
 }
@@ -92,7 +184,7 @@ impl<'a> UpdatableRecursively<'a> for ElementaryAutomataRule {
     fn update_recursively(&mut self, _arg: Self::UpdateArg) {}
 }
 
-#[derive(Debug, Clone, Copy, Generatable, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Generatable, Serialize, Deserialize, PartialEq, Eq)]
 #[mutagen(gen_arg = type ProtoGenArg<'a>)]
 pub enum PixelNeighbourhood {
     Vertical,
@@ -113,6 +205,51 @@ pub enum PixelNeighbourhood {
 }
 
 impl PixelNeighbourhood {
+    pub fn values() -> [Self; 15] {
+        [
+            PixelNeighbourhood::Vertical,
+            PixelNeighbourhood::Horizontal,
+            PixelNeighbourhood::DiagLeft,
+            PixelNeighbourhood::DiagRight,
+            PixelNeighbourhood::Melt,
+            PixelNeighbourhood::BigMelt,
+            PixelNeighbourhood::VonNeumann,
+            PixelNeighbourhood::AntiVonNeumann,
+            PixelNeighbourhood::Cross,
+            PixelNeighbourhood::Moore,
+            PixelNeighbourhood::Spiral,
+            PixelNeighbourhood::Diamond,
+            PixelNeighbourhood::Circle,
+            PixelNeighbourhood::Flower,
+            PixelNeighbourhood::Square,
+        ]
+    }
+
+    /// Number of neighbours, must always match the length of offsets()
+    pub const fn size(&self) -> usize {
+        match self {
+            PixelNeighbourhood::Vertical => 2,
+            PixelNeighbourhood::Horizontal => 2,
+            PixelNeighbourhood::DiagLeft => 2,
+            PixelNeighbourhood::DiagRight => 2,
+            PixelNeighbourhood::Melt => 3,
+            PixelNeighbourhood::BigMelt => 6,
+            PixelNeighbourhood::VonNeumann => 4,
+            PixelNeighbourhood::AntiVonNeumann => 4,
+            PixelNeighbourhood::Cross => 8,
+            PixelNeighbourhood::Moore => 8,
+            PixelNeighbourhood::Spiral => 8,
+            PixelNeighbourhood::Diamond => 8,
+            PixelNeighbourhood::Circle => 12,
+            PixelNeighbourhood::Flower => 12,
+            PixelNeighbourhood::Square => 16,
+        }
+    }
+
+    pub const fn max_size() -> usize {
+        16
+    }
+
     pub fn offsets(&self) -> &'static [(isize, isize)] {
         match self {
             PixelNeighbourhood::Vertical => &[(0, -1), (0, 1)],
@@ -124,7 +261,7 @@ impl PixelNeighbourhood {
                 &[(-1, -1), (0, -1), (1, -1), (-1, -2), (0, -2), (1, -2)]
             }
             PixelNeighbourhood::VonNeumann => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
-            PixelNeighbourhood::AntiVonNeumann => &[(-1, -1), (1, -1), (1, -1), (1, 1)],
+            PixelNeighbourhood::AntiVonNeumann => &[(-1, -1), (1, -1), (-1, 1), (1, 1)],
             PixelNeighbourhood::Cross => &[
                 (-1, 0),
                 (-2, 0),
@@ -220,7 +357,7 @@ impl PixelNeighbourhood {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NeighbourCountAutomataRule {
     pub neighbourhood: PixelNeighbourhood,
     pub truth_table: Array3<BitColor>,
@@ -231,7 +368,7 @@ impl<'a> Generatable<'a> for NeighbourCountAutomataRule {
 
     fn generate_rng<R: Rng + ?Sized>(rng: &mut R, mut arg: Self::GenArg) -> Self {
         let neighbourhood = PixelNeighbourhood::generate_rng(rng, arg.reborrow());
-        let n = neighbourhood.offsets().len() + 1;
+        let n = neighbourhood.size() + 1;
 
         Self {
             neighbourhood,
@@ -242,15 +379,52 @@ impl<'a> Generatable<'a> for NeighbourCountAutomataRule {
     }
 }
 
+impl Crossover for NeighbourCountAutomataRule {
+    /// Splits the truth table into eight blocks around a random pivot, taking each block from a random parent.
+    /// If the neighbourhoods differ, one parent's neighbourhood is kept and the other's counts are rescaled to fit.
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        let (a, b) = if rng.gen::<bool>() { (a, b) } else { (b, a) };
+
+        let n = a.neighbourhood.size();
+        let other_n = b.neighbourhood.size();
+
+        let pivot = [
+            rng.gen_range(0..=n),
+            rng.gen_range(0..=n),
+            rng.gen_range(0..=n),
+        ];
+        let block_parents: [bool; 8] = rng.gen();
+
+        Self {
+            neighbourhood: a.neighbourhood,
+            truth_table: Array3::from_shape_fn((n + 1, n + 1, n + 1), |(r, g, b_idx)| {
+                let block = usize::from(r >= pivot[0])
+                    | usize::from(g >= pivot[1]) << 1
+                    | usize::from(b_idx >= pivot[2]) << 2;
+
+                if block_parents[block] {
+                    a.truth_table[[r, g, b_idx]]
+                } else {
+                    b.truth_table[[
+                        map_neighbour_count(r, n, other_n),
+                        map_neighbour_count(g, n, other_n),
+                        map_neighbour_count(b_idx, n, other_n),
+                    ]]
+                }
+            }),
+        }
+    }
+}
+
 impl<'a> Mutatable<'a> for NeighbourCountAutomataRule {
     type MutArg = ProtoMutArg<'a>;
 
     fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: Self::MutArg) {
         // *self = Self::generate_rng(rng, arg.into());
-        let n = self.neighbourhood.offsets().len() + 1;
-        let index_r = thread_rng().gen::<usize>() % n;
-        let index_g = thread_rng().gen::<usize>() % n;
-        let index_b = thread_rng().gen::<usize>() % n;
+        let n = self.neighbourhood.size() + 1;
+        let index_r = rng.gen::<usize>() % n;
+        let index_g = rng.gen::<usize>() % n;
+        let index_b = rng.gen::<usize>() % n;
 
         self.truth_table[[index_r, index_g, index_b]] = BitColor::generate_rng(rng, arg.into());
     }
@@ -266,7 +440,7 @@ impl<'a> UpdatableRecursively<'a> for NeighbourCountAutomataRule {
     fn update_recursively(&mut self, _arg: Self::UpdateArg) {}
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IndivAutomataRule {
     pub neighbourhood: PixelNeighbourhood,
     pub rules: Vec<LifeLikeTable>,
@@ -277,7 +451,7 @@ impl<'a> Generatable<'a> for IndivAutomataRule {
 
     fn generate_rng<R: Rng + ?Sized>(rng: &mut R, mut arg: Self::GenArg) -> Self {
         let neighbourhood = PixelNeighbourhood::generate_rng(rng, arg.reborrow());
-        let n = neighbourhood.offsets().len();
+        let n = neighbourhood.size();
 
         Self {
             neighbourhood,
@@ -288,15 +462,39 @@ impl<'a> Generatable<'a> for IndivAutomataRule {
     }
 }
 
+impl Crossover for IndivAutomataRule {
+    /// Mixes the tables for each neighbour count.
+    /// If the neighbourhoods differ, one parent's neighbourhood is kept and the other's counts are rescaled to fit.
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        let (a, b) = if rng.gen::<bool>() { (a, b) } else { (b, a) };
+
+        let n = a.neighbourhood.size();
+        let other_n = b.neighbourhood.size();
+
+        Self {
+            neighbourhood: a.neighbourhood,
+            rules: (0..=n)
+                .map(|i| {
+                    LifeLikeTable::crossover(
+                        &a.rules[i],
+                        &b.rules[map_neighbour_count(i, n, other_n)],
+                        rng,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Mutatable<'a> for IndivAutomataRule {
     type MutArg = ProtoMutArg<'a>;
 
     fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: Self::MutArg) {
-        if thread_rng().gen::<bool>() {
+        if rng.gen::<bool>() {
             *self = Self::generate_rng(rng, arg.into());
         } else {
-            self.rules[thread_rng().gen::<usize>() % self.neighbourhood.offsets().len()]
-                .mutate_rng(rng, arg);
+            let index = rng.gen_range(0..self.rules.len());
+            self.rules[index].mutate_rng(rng, arg);
         }
     }
 }
@@ -311,7 +509,7 @@ impl<'a> UpdatableRecursively<'a> for IndivAutomataRule {
     fn update_recursively(&mut self, _arg: Self::UpdateArg) {}
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LifeLikeAutomataRule {
     // pub neighbourhood: PixelNeighbourhood,
     pub color_order: [BitColor; 8],
@@ -321,19 +519,70 @@ pub struct LifeLikeAutomataRule {
     pub color_rules: [IndivAutomataRule; 8],
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Generatable, Mutatable)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Generatable, Mutatable)]
 #[mutagen(gen_arg = type ProtoGenArg<'a>, mut_arg = type ProtoMutArg<'a>)]
 pub struct LifeLikeTable {
     pub birth: Boolean,
     pub survival: Boolean,
 }
 
+impl LifeLikeAutomataRule {
+    /// The order in which colours are assigned to their rules
+    pub fn color_order(&self) -> &[BitColor; 8] {
+        &self.color_order
+    }
+}
+
+impl Crossover for LifeLikeTable {
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        Self {
+            birth: if rng.gen::<bool>() { a.birth } else { b.birth },
+            survival: if rng.gen::<bool>() {
+                a.survival
+            } else {
+                b.survival
+            },
+        }
+    }
+}
+
+impl Crossover for LifeLikeAutomataRule {
+    /// Takes each colour rule from a random parent, and splices the colour orders
+    /// by keeping a prefix of one parent and filling the rest in the other parent's order.
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        let cut = rng.gen_range(0..=8);
+
+        let mut color_order = a.color_order;
+        let mut remaining = b
+            .color_order
+            .iter()
+            .filter(|c| !a.color_order[0..cut].contains(c));
+
+        for color in color_order[cut..].iter_mut() {
+            *color = *remaining.next().unwrap();
+        }
+
+        let mut color_rules = a.color_rules.clone();
+
+        for (rule, other) in color_rules.iter_mut().zip(b.color_rules.iter()) {
+            if rng.gen::<bool>() {
+                *rule = other.clone();
+            }
+        }
+
+        Self {
+            color_order,
+            color_rules,
+        }
+    }
+}
+
 impl<'a> Generatable<'a> for LifeLikeAutomataRule {
     type GenArg = ProtoGenArg<'a>;
 
     fn generate_rng<R: Rng + ?Sized>(rng: &mut R, mut arg: Self::GenArg) -> Self {
         let mut color_order = BitColor::values();
-        color_order.shuffle(rng);
+        seeded_shuffle(&mut color_order, rng);
 
         Self {
             color_order,
@@ -355,10 +604,10 @@ impl<'a> Mutatable<'a> for LifeLikeAutomataRule {
     type MutArg = ProtoMutArg<'a>;
 
     fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: Self::MutArg) {
-        if thread_rng().gen::<bool>() {
+        if rng.gen::<bool>() {
             *self = Self::generate_rng(rng, arg.into());
         } else {
-            self.color_rules[thread_rng().gen::<usize>() % 8].mutate_rng(rng, arg);
+            self.color_rules[rng.gen::<usize>() % 8].mutate_rng(rng, arg);
         }
     }
 }
@@ -373,10 +622,274 @@ impl<'a> UpdatableRecursively<'a> for LifeLikeAutomataRule {
     fn update_recursively(&mut self, _arg: Self::UpdateArg) {}
 }
 
+/// Rescales a neighbour count from a neighbourhood of one size to another
+fn map_neighbour_count(count: usize, from_size: usize, to_size: usize) -> usize {
+    (count * to_size + from_size / 2)
+        .checked_div(from_size)
+        .unwrap_or(0)
+}
+
+/// Runs a rule step over an initial state, returning the history of generations starting with the initial state
+pub fn run_automata<F>(
+    initial: Array2<BitColor>,
+    generations: usize,
+    step: F,
+) -> Vec<Array2<BitColor>>
+where
+    F: Fn(&Array2<BitColor>) -> Array2<BitColor>,
+{
+    let mut history: Vec<Array2<BitColor>> = Vec::with_capacity(generations);
+
+    if generations > 0 {
+        history.push(initial);
+    }
+
+    while history.len() < generations {
+        let next = step(history.last().unwrap());
+        history.push(next);
+    }
+
+    history
+}
+
 #[cfg(test)]
 mod tests {
+    use nalgebra::Point2;
+
     use super::*;
 
+    #[test]
+    fn test_neighbourhood_sizes() {
+        for neighbourhood in PixelNeighbourhood::values() {
+            assert_eq!(
+                neighbourhood.size(),
+                neighbourhood.offsets().len(),
+                "Mismatched size for {:?}",
+                neighbourhood
+            );
+            assert!(neighbourhood.size() <= PixelNeighbourhood::max_size());
+
+            let offsets = neighbourhood.offsets();
+            for (i, offset) in offsets.iter().enumerate() {
+                assert!(
+                    !offsets[i + 1..].contains(offset),
+                    "Duplicate offset {:?} in {:?}",
+                    offset,
+                    neighbourhood
+                );
+                assert_ne!(*offset, (0, 0), "{:?} includes its own cell", neighbourhood);
+            }
+        }
+
+        assert_eq!(
+            PixelNeighbourhood::AntiVonNeumann.offsets(),
+            &[(-1, -1), (1, -1), (-1, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_wolfram_code_round_trip() {
+        for code in 0..=255 {
+            let rule = ElementaryAutomataRule::from_wolfram_code(code);
+
+            assert_eq!(rule.to_wolfram_code(), code);
+            assert_eq!(rule.rule_number(), code);
+        }
+
+        let mutate = |seed: u64| {
+            let mut rng = DeterministicRng::seed_from_u64(seed);
+            let mut rule = ElementaryAutomataRule::from_wolfram_code(110);
+
+            (0..50)
+                .map(|_| {
+                    rule.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+                    rule.to_wolfram_code()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(mutate(3), mutate(3));
+        assert_ne!(mutate(3), mutate(4));
+    }
+
+    #[test]
+    fn test_rule_90_spacetime_is_sierpinski() {
+        let (width, generations) = (33, 16);
+        let center = width / 2;
+        let initial = Array1::from_shape_fn(width, |x| Boolean::new(x == center));
+
+        let diagram = ElementaryAutomataRule::from_wolfram_code(90).spacetime(
+            width,
+            generations,
+            &initial,
+            false,
+        );
+
+        assert_eq!((diagram.width(), diagram.height()), (width, generations));
+
+        for y in 0..generations {
+            for x in 0..width {
+                assert_eq!(
+                    diagram[Point2::new(x, y)],
+                    diagram[Point2::new(width - 1 - x, y)]
+                );
+
+                // Pascal's triangle mod 2
+                let offset = x as isize - center as isize;
+                let expected = offset.unsigned_abs() <= y && (offset + y as isize) % 2 == 0 && {
+                    let k = ((offset + y as isize) / 2) as usize;
+                    k & y == k
+                };
+
+                assert_eq!(
+                    diagram[Point2::new(x, y)],
+                    if expected {
+                        BitColor::White
+                    } else {
+                        BitColor::Black
+                    },
+                    "Mismatch at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_life_like_color_order_is_seeded() {
+        let generate = |seed: u64| {
+            LifeLikeAutomataRule::generate_rng(
+                &mut DeterministicRng::seed_from_u64(seed),
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            )
+        };
+
+        for seed in 0..10 {
+            let rule = generate(seed);
+            assert_eq!(rule.color_order(), generate(seed).color_order());
+
+            let mut sorted = rule.color_order().to_vec();
+            sorted.sort_by_key(|c| c.to_index());
+            assert_eq!(sorted, BitColor::values().to_vec());
+        }
+
+        assert!((1..10).any(|seed| generate(seed).color_order() != generate(0).color_order()));
+    }
+
+    #[test]
+    fn test_crossover_identical_parents() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..10 {
+            let elementary = ElementaryAutomataRule::generate_rng(
+                &mut rng,
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            );
+            assert_eq!(
+                ElementaryAutomataRule::crossover(&elementary, &elementary, &mut rng),
+                elementary
+            );
+
+            let neighbour_count = NeighbourCountAutomataRule::generate_rng(
+                &mut rng,
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            );
+            assert_eq!(
+                NeighbourCountAutomataRule::crossover(&neighbour_count, &neighbour_count, &mut rng),
+                neighbour_count
+            );
+
+            let life_like = LifeLikeAutomataRule::generate_rng(
+                &mut rng,
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            );
+            assert_eq!(
+                LifeLikeAutomataRule::crossover(&life_like, &life_like, &mut rng),
+                life_like
+            );
+        }
+    }
+
+    #[test]
+    fn test_elementary_crossover_bits() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        let a = ElementaryAutomataRule::from_wolfram_code(0b1010_1010);
+        let b = ElementaryAutomataRule::from_wolfram_code(0b1100_1100);
+
+        for _ in 0..100 {
+            let child = ElementaryAutomataRule::crossover(&a, &b, &mut rng);
+
+            for i in 0..8 {
+                assert!(child.pattern[i] == a.pattern[i] || child.pattern[i] == b.pattern[i]);
+            }
+        }
+
+        let zero = ElementaryAutomataRule::from_wolfram_code(0);
+        let full = ElementaryAutomataRule::from_wolfram_code(255);
+
+        let children: Vec<_> = (0..100)
+            .map(|_| ElementaryAutomataRule::crossover(&zero, &full, &mut rng))
+            .collect();
+
+        assert!(children
+            .iter()
+            .any(|c| c.pattern.iter().any(|b| b.into_inner())));
+        assert!(children
+            .iter()
+            .any(|c| c.pattern.iter().any(|b| !b.into_inner())));
+    }
+
+    #[test]
+    fn test_crossover_neighbourhood_mismatch() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        let table = |n: usize| {
+            (0..=n)
+                .map(|i| LifeLikeTable {
+                    birth: Boolean::new(i % 2 == 0),
+                    survival: Boolean::new(i % 3 == 0),
+                })
+                .collect()
+        };
+
+        let small = IndivAutomataRule {
+            neighbourhood: PixelNeighbourhood::Vertical,
+            rules: table(PixelNeighbourhood::Vertical.size()),
+        };
+        let big = IndivAutomataRule {
+            neighbourhood: PixelNeighbourhood::Square,
+            rules: table(PixelNeighbourhood::Square.size()),
+        };
+
+        let small_count = NeighbourCountAutomataRule {
+            neighbourhood: PixelNeighbourhood::Vertical,
+            truth_table: Array3::from_elem((3, 3, 3), BitColor::Red),
+        };
+        let big_count = NeighbourCountAutomataRule {
+            neighbourhood: PixelNeighbourhood::Square,
+            truth_table: Array3::from_elem((17, 17, 17), BitColor::Blue),
+        };
+
+        for _ in 0..100 {
+            let child = IndivAutomataRule::crossover(&small, &big, &mut rng);
+            assert_eq!(child.rules.len(), child.neighbourhood.size() + 1);
+
+            let child = NeighbourCountAutomataRule::crossover(&big_count, &small_count, &mut rng);
+            let n = child.neighbourhood.size() + 1;
+            assert_eq!(child.truth_table.dim(), (n, n, n));
+        }
+    }
+
     #[test]
     fn test_rule_110() {
         let rule = ElementaryAutomataRule::from_wolfram_code(110);
@@ -461,4 +974,59 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn test_seeded_mutation_is_deterministic() {
+        fn mutated<
+            T: for<'a> Generatable<'a, GenArg = ProtoGenArg<'a>>
+                + for<'a> Mutatable<'a, MutArg = ProtoMutArg<'a>>,
+        >(
+            seed: u64,
+        ) -> T {
+            let mut rng = DeterministicRng::seed_from_u64(seed);
+            let mut value = T::generate_rng(
+                &mut rng,
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            );
+
+            for _ in 0..20 {
+                value.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+            }
+
+            value
+        }
+
+        for seed in 0..10 {
+            assert_eq!(
+                mutated::<ElementaryAutomataRule>(seed),
+                mutated::<ElementaryAutomataRule>(seed)
+            );
+            assert_eq!(
+                mutated::<NeighbourCountAutomataRule>(seed),
+                mutated::<NeighbourCountAutomataRule>(seed)
+            );
+            assert_eq!(
+                mutated::<IndivAutomataRule>(seed),
+                mutated::<IndivAutomataRule>(seed)
+            );
+            assert_eq!(
+                mutated::<LifeLikeAutomataRule>(seed),
+                mutated::<LifeLikeAutomataRule>(seed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_automata() {
+        let initial = Array2::from_shape_fn((4, 6), |(y, x)| BitColor::from_index((x + y) % 8));
+
+        let history = run_automata(initial.clone(), 5, |state| state.clone());
+
+        assert_eq!(history.len(), 5);
+        assert!(history.iter().all(|generation| *generation == initial));
+
+        assert!(run_automata(initial, 0, |state| state.clone()).is_empty());
+    }
 }
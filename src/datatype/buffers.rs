@@ -5,32 +5,52 @@ use std::{
 };
 
 use bresenham::Bresenham;
+use failure::{ensure, Fallible};
 use mutagen::{Generatable, Mutatable, Reborrow, Updatable, UpdatableRecursively};
 use nalgebra::*;
-use ndarray::prelude::*;
+use ndarray::{prelude::*, Zip};
 use rand::prelude::*;
-use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
+use serde::{
+    de::{self, Deserializer},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
 
 use crate::prelude::*;
 
 pub struct Buffer<T> {
     array: Array2<T>,
+    params: BufferGenParams,
 }
 
 impl<T> Buffer<T> {
     pub fn new(array: Array2<T>) -> Self {
-        Self { array }
+        Self::with_params(array, BufferGenParams::default())
+    }
+
+    pub fn with_params(array: Array2<T>, params: BufferGenParams) -> Self {
+        Self { array, params }
+    }
+
+    pub fn params(&self) -> BufferGenParams {
+        self.params
     }
 
+    /// The cell that coords fall inside, so each cell covers [index, index + 1) of the scaled range
     pub fn point_to_uint(&self, coords: SNPoint) -> Point2<usize> {
-        let (height, width) = self.array.dim();
+        Point2::new(self.x_to_uint(coords.x()), self.y_to_uint(coords.y()))
+    }
 
-        Point2::new(
-            ((coords.x().to_unsigned().into_inner() * width as f32).round() as usize)
-                .min(width - 1),
-            ((coords.y().to_unsigned().into_inner() * height as f32).round() as usize)
-                .min(height - 1),
-        )
+    pub fn x_to_uint(&self, x: SNFloat) -> usize {
+        let width = self.width();
+
+        ((x.to_unsigned().into_inner() * width as f32).floor() as usize).min(width - 1)
+    }
+
+    pub fn y_to_uint(&self, y: SNFloat) -> usize {
+        let height = self.height();
+
+        ((y.to_unsigned().into_inner() * height as f32).floor() as usize).min(height - 1)
     }
 
     pub fn width(&self) -> usize {
@@ -43,12 +63,62 @@ impl<T> Buffer<T> {
 
     pub fn info(&self) -> BufferInfo {
         let (height, width) = self.array.dim();
-        BufferInfo { width, height }
+        BufferInfo {
+            width,
+            height,
+            params: self.params,
+        }
+    }
+
+    /// The center of the cell at (x, y), in buffer coordinates
+    pub fn cell_center(&self, x: usize, y: usize) -> SNPoint {
+        cell_center(x, y, self.width(), self.height())
+    }
+
+    pub fn iter_points(&self) -> impl Iterator<Item = (SNPoint, &T)> {
+        let (width, height) = (self.width(), self.height());
+
+        self.array
+            .indexed_iter()
+            .map(move |((y, x), value)| (cell_center(x, y, width, height), value))
+    }
+
+    pub fn iter_points_mut(&mut self) -> impl Iterator<Item = (SNPoint, &mut T)> {
+        let (width, height) = (self.width(), self.height());
+
+        self.array
+            .indexed_iter_mut()
+            .map(move |((y, x), value)| (cell_center(x, y, width, height), value))
     }
 }
 
+fn cell_center(x: usize, y: usize, width: usize, height: usize) -> SNPoint {
+    let to_snfloat =
+        |i: usize, len: usize| UNFloat::new_clamped((i as f32 + 0.5) / len as f32).to_signed();
+
+    SNPoint::from_snfloats(to_snfloat(x, width), to_snfloat(y, height))
+}
+
 impl<T: Clone> Buffer<T> {
+    /// Resizes in place, keeping existing cells and extending the edge cells into any new space
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let (old_height, old_width) = self.array.dim();
+
+        if (height, width) == (old_height, old_width) {
+            return;
+        }
+
+        self.array = Array2::from_shape_fn((height, width), |(y, x)| {
+            self.array[[y.min(old_height - 1), x.min(old_width - 1)]].clone()
+        });
+    }
+
     pub fn draw_line(&mut self, from: SNPoint, to: SNPoint, value: T) {
+        self.draw_line_with(from, to, |_| value.clone());
+    }
+
+    /// Like draw_line, but each pixel on the line is replaced by f applied to its current value
+    pub fn draw_line_with(&mut self, from: SNPoint, to: SNPoint, mut f: impl FnMut(&T) -> T) {
         let from_uint = self.point_to_uint(from);
         let from_bresenham = (from_uint.x as isize, from_uint.y as isize);
 
@@ -59,7 +129,7 @@ impl<T: Clone> Buffer<T> {
             Bresenham::new(from_bresenham, to_bresenham).chain(iter::once(to_bresenham))
         {
             let point_uint = Point2::new(point_bresenham.0 as usize, point_bresenham.1 as usize);
-            self[point_uint] = value.clone();
+            self[point_uint] = f(&self[point_uint]);
         }
     }
 
@@ -67,6 +137,734 @@ impl<T: Clone> Buffer<T> {
         let point_uint = self.point_to_uint(pos);
         self[point_uint] = value;
     }
+
+    pub fn draw_dot_with(&mut self, pos: SNPoint, f: impl FnOnce(&T) -> T) {
+        let point_uint = self.point_to_uint(pos);
+        self[point_uint] = f(&self[point_uint]);
+    }
+
+    /// The center cell of a circle and the offsets of its first quadrant from the midpoint
+    /// ellipse algorithm. The radius is scaled by the buffer width horizontally and the height
+    /// vertically, as SNFloat coordinates are in point_to_uint, so a non-square buffer draws an
+    /// ellipse. A zero radius gives just the center.
+    fn circle_quadrant(
+        &self,
+        center: SNPoint,
+        radius: UNFloat,
+    ) -> (Point2<isize>, Vec<(isize, isize)>) {
+        let center_uint = self.point_to_uint(center);
+        let scale = |len: usize| (radius.into_inner() * len as f32 * 0.5).round() as isize;
+        let (rx, ry) = (scale(self.width()), scale(self.height()));
+
+        let center = Point2::new(center_uint.x as isize, center_uint.y as isize);
+
+        if ry == 0 {
+            return (center, (0..=rx).map(|x| (x, 0)).collect());
+        }
+
+        let (rx2, ry2) = (rx * rx, ry * ry);
+        let mut quadrant = Vec::new();
+        let (mut x, mut y) = (0, ry);
+        let (mut px, mut py) = (0, 2 * rx2 * ry);
+
+        // Decision values are kept at four times their size to stay in integers.
+        // The first region steps along x while the slope is shallow...
+        let mut err = 4 * ry2 - 4 * rx2 * ry + rx2;
+
+        while px < py {
+            quadrant.push((x, y));
+
+            x += 1;
+            px += 2 * ry2;
+
+            if err < 0 {
+                err += 4 * (ry2 + px);
+            } else {
+                y -= 1;
+                py -= 2 * rx2;
+                err += 4 * (ry2 + px - py);
+            }
+        }
+
+        // ...and the second steps along y once it is steep
+        let mut err = ry2 * (2 * x + 1) * (2 * x + 1) + 4 * rx2 * (y - 1) * (y - 1) - 4 * rx2 * ry2;
+
+        while y >= 0 {
+            quadrant.push((x, y));
+
+            y -= 1;
+            py -= 2 * rx2;
+
+            if err > 0 {
+                err += 4 * (rx2 - py);
+            } else {
+                x += 1;
+                px += 2 * ry2;
+                err += 4 * (rx2 - py + px);
+            }
+        }
+
+        (center, quadrant)
+    }
+
+    /// Draws a circle outline, skipping any part of it that falls outside the buffer
+    pub fn draw_circle(&mut self, center: SNPoint, radius: UNFloat, value: T) {
+        let (center, quadrant) = self.circle_quadrant(center, radius);
+        let (width, height) = (self.width() as isize, self.height() as isize);
+
+        let mut plot = |x: isize, y: isize| {
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                self[Point2::new(x as usize, y as usize)] = value.clone();
+            }
+        };
+
+        for (dx, dy) in quadrant {
+            plot(center.x + dx, center.y + dy);
+            plot(center.x - dx, center.y + dy);
+            plot(center.x + dx, center.y - dy);
+            plot(center.x - dx, center.y - dy);
+        }
+    }
+
+    /// Fills the disc bounded by draw_circle's outline with horizontal spans, clipped to the buffer
+    pub fn draw_filled_circle(&mut self, center: SNPoint, radius: UNFloat, value: T) {
+        let (center, quadrant) = self.circle_quadrant(center, radius);
+        let (width, height) = (self.width() as isize, self.height() as isize);
+
+        for (x, y) in quadrant {
+            for (half_width, dy) in [(x, y), (x, -y)] {
+                let row = center.y + dy;
+
+                if !(0..height).contains(&row) {
+                    continue;
+                }
+
+                let left = (center.x - half_width).max(0);
+                let right = (center.x + half_width).min(width - 1);
+
+                for column in left..=right {
+                    self[Point2::new(column as usize, row as usize)] = value.clone();
+                }
+            }
+        }
+    }
+
+    /// Fills every cell between the two corners inclusive, in either order
+    pub fn draw_filled_rect(&mut self, corner_a: SNPoint, corner_b: SNPoint, value: T) {
+        let a = self.point_to_uint(corner_a);
+        let b = self.point_to_uint(corner_b);
+
+        for y in a.y.min(b.y)..=a.y.max(b.y) {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                self[Point2::new(x, y)] = value.clone();
+            }
+        }
+    }
+
+    /// Draws each edge between points of the set, e.g. from PointSet::nearest_neighbour_graph
+    pub fn draw_point_set_graph(&mut self, set: &PointSet, edges: &[(usize, usize)], value: T) {
+        for &(a, b) in edges {
+            self.draw_line(set[a], set[b], value.clone());
+        }
+    }
+
+    pub fn row(&self, y: usize) -> Vec<T> {
+        self.array.row(y).to_vec()
+    }
+
+    pub fn column(&self, x: usize) -> Vec<T> {
+        self.array.column(x).to_vec()
+    }
+
+    pub fn row_at(&self, y: SNFloat) -> Vec<T> {
+        self.row(self.y_to_uint(y))
+    }
+
+    pub fn column_at(&self, x: SNFloat) -> Vec<T> {
+        self.column(self.x_to_uint(x))
+    }
+
+    pub fn set_row(&mut self, y: usize, values: &[T]) -> Fallible<()> {
+        ensure!(
+            values.len() == self.width(),
+            "Row length mismatch: expected {}, got {}",
+            self.width(),
+            values.len()
+        );
+
+        self.array.row_mut(y).assign(&ArrayView1::from(values));
+        Ok(())
+    }
+
+    pub fn set_column(&mut self, x: usize, values: &[T]) -> Fallible<()> {
+        ensure!(
+            values.len() == self.height(),
+            "Column length mismatch: expected {}, got {}",
+            self.height(),
+            values.len()
+        );
+
+        self.array.column_mut(x).assign(&ArrayView1::from(values));
+        Ok(())
+    }
+
+    pub fn set_row_at(&mut self, y: SNFloat, values: &[T]) -> Fallible<()> {
+        self.set_row(self.y_to_uint(y), values)
+    }
+
+    pub fn set_column_at(&mut self, x: SNFloat, values: &[T]) -> Fallible<()> {
+        self.set_column(self.x_to_uint(x), values)
+    }
+
+    /// Shifts every cell right by dx and down by dy, wrapping cells pushed off one edge around
+    /// to the other. Useful for moving the seams of a tileable pattern into view.
+    pub fn offset_wrapped(&self, dx: isize, dy: isize) -> Self {
+        let (height, width) = self.array.dim();
+
+        Self::with_params(
+            Array2::from_shape_fn((height, width), |(y, x)| {
+                let source_x = (x as isize - dx).rem_euclid(width as isize) as usize;
+                let source_y = (y as isize - dy).rem_euclid(height as isize) as usize;
+
+                self.array[[source_y, source_x]].clone()
+            }),
+            self.params,
+        )
+    }
+}
+
+impl Buffer<BitColor> {
+    pub fn color_histogram(&self) -> [usize; 8] {
+        color_histogram(&self.array)
+    }
+
+    pub fn dominant_color(&self) -> BitColor {
+        dominant_color(&self.array)
+    }
+
+    pub fn fraction_alive(&self) -> UNFloat {
+        fraction_alive(&self.array)
+    }
+
+    pub fn component_fractions(&self) -> [UNFloat; 3] {
+        component_fractions(&self.array)
+    }
+
+    /// Labels each 4-connected region of identical colour with a unique id, returning the labels and the number of regions
+    pub fn label_components(&self) -> (Buffer<u32>, usize) {
+        let (height, width) = self.array.dim();
+
+        let mut labels: Array2<Option<u32>> = Array2::from_elem((height, width), None);
+        let mut count = 0;
+        let mut stack = Vec::new();
+
+        for start_y in 0..height {
+            for start_x in 0..width {
+                if labels[[start_y, start_x]].is_some() {
+                    continue;
+                }
+
+                let label = count as u32;
+                let color = self.array[[start_y, start_x]];
+
+                labels[[start_y, start_x]] = Some(label);
+                stack.push((start_x, start_y));
+
+                while let Some((x, y)) = stack.pop() {
+                    let neighbours = [
+                        (x.wrapping_sub(1), y),
+                        (x + 1, y),
+                        (x, y.wrapping_sub(1)),
+                        (x, y + 1),
+                    ];
+
+                    for (nx, ny) in neighbours {
+                        if nx < width
+                            && ny < height
+                            && labels[[ny, nx]].is_none()
+                            && self.array[[ny, nx]] == color
+                        {
+                            labels[[ny, nx]] = Some(label);
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+
+                count += 1;
+            }
+        }
+
+        (Buffer::new(labels.mapv(Option::unwrap)), count)
+    }
+
+    /// Looks up each cell's colour in a palette indexed by BitColor::to_index, instead of BitColor::get_color
+    pub fn to_float_buffer(&self, palette: &[ByteColor; 8]) -> Buffer<FloatColor> {
+        Buffer::new(self.array.map(|c| FloatColor::from(palette[c.to_index()])))
+    }
+
+    /// Sets each colour component if it is set in the cell or any of its neighbours, wrapping at the edges
+    pub fn dilate(&self, neighbourhood: &PixelNeighbourhood) -> Self {
+        self.morph(neighbourhood, |a, b| a || b)
+    }
+
+    /// Keeps each colour component only if it is set in the cell and all of its neighbours, wrapping at the edges
+    pub fn erode(&self, neighbourhood: &PixelNeighbourhood) -> Self {
+        self.morph(neighbourhood, |a, b| a && b)
+    }
+
+    /// Erodes then dilates, removing specks smaller than the neighbourhood
+    pub fn open(&self, neighbourhood: &PixelNeighbourhood) -> Self {
+        self.erode(neighbourhood).dilate(neighbourhood)
+    }
+
+    /// Dilates then erodes, filling holes smaller than the neighbourhood
+    pub fn close(&self, neighbourhood: &PixelNeighbourhood) -> Self {
+        self.dilate(neighbourhood).erode(neighbourhood)
+    }
+
+    fn morph(&self, neighbourhood: &PixelNeighbourhood, combine: fn(bool, bool) -> bool) -> Self {
+        let (height, width) = self.array.dim();
+
+        Self::new(Array2::from_shape_fn((height, width), |(y, x)| {
+            let components = neighbourhood.offsets().iter().fold(
+                self.array[[y, x]].to_components(),
+                |acc, &(dx, dy)| {
+                    let nx = (x as isize + dx).rem_euclid(width as isize) as usize;
+                    let ny = (y as isize + dy).rem_euclid(height as isize) as usize;
+                    let other = self.array[[ny, nx]].to_components();
+
+                    [0, 1, 2].map(|i| combine(acc[i], other[i]))
+                },
+            );
+
+            BitColor::from_components(components)
+        }))
+    }
+}
+
+/// Number of cells of each colour, indexed by BitColor::to_index
+pub fn color_histogram(array: &Array2<BitColor>) -> [usize; 8] {
+    let mut histogram = [0; 8];
+
+    for color in array.iter() {
+        histogram[color.to_index()] += 1;
+    }
+
+    histogram
+}
+
+/// Most common colour, ties resolved towards the lowest index
+pub fn dominant_color(array: &Array2<BitColor>) -> BitColor {
+    let histogram = color_histogram(array);
+
+    BitColor::from_index((0..8).rev().max_by_key(|&i| histogram[i]).unwrap())
+}
+
+/// Fraction of cells that aren't black
+pub fn fraction_alive(array: &Array2<BitColor>) -> UNFloat {
+    let histogram = color_histogram(array);
+    let total = array.len().max(1);
+
+    UNFloat::new((total - histogram[BitColor::Black.to_index()]) as f32 / total as f32)
+}
+
+/// Fraction of cells with each of the red, green and blue components set
+pub fn component_fractions(array: &Array2<BitColor>) -> [UNFloat; 3] {
+    let histogram = color_histogram(array);
+    let total = array.len().max(1);
+
+    let mut counts = [0; 3];
+
+    for (i, count) in histogram.iter().enumerate() {
+        let components = BitColor::from_index(i).to_components();
+
+        for c in 0..3 {
+            if components[c] {
+                counts[c] += count;
+            }
+        }
+    }
+
+    counts.map(|count| UNFloat::new(count as f32 / total as f32))
+}
+
+impl Buffer<FloatColor> {
+    pub fn row_luminance(&self, y: usize) -> Vec<UNFloat> {
+        self.array
+            .row(y)
+            .iter()
+            .map(|c| c.get_luminance())
+            .collect()
+    }
+
+    pub fn column_luminance(&self, x: usize) -> Vec<UNFloat> {
+        self.array
+            .column(x)
+            .iter()
+            .map(|c| c.get_luminance())
+            .collect()
+    }
+
+    /// Replaces each pixel with above if its luminance reaches level, and below otherwise
+    pub fn threshold(&self, level: UNFloat, below: FloatColor, above: FloatColor) -> Self {
+        Self::new(self.array.map(|c| {
+            if c.get_luminance().into_inner() >= level.into_inner() {
+                above
+            } else {
+                below
+            }
+        }))
+    }
+
+    /// Quantizes each colour channel into levels + 1 evenly spaced steps, leaving alpha untouched.
+    /// Zero levels is a single step, so every channel becomes 0.
+    pub fn posterize(&self, levels: Nibble) -> Self {
+        let levels = f32::from(levels.into_inner());
+        let quantize = |v: UNFloat| {
+            if levels == 0.0 {
+                UNFloat::ZERO
+            } else {
+                UNFloat::new((v.into_inner() * levels).round() / levels)
+            }
+        };
+
+        Self::new(self.array.map(|c| FloatColor {
+            r: quantize(c.r),
+            g: quantize(c.g),
+            b: quantize(c.b),
+            a: c.a,
+        }))
+    }
+
+    /// Reduces to the BitColor palette with Floyd-Steinberg error diffusion, in scanline order
+    pub fn to_bitcolor_floyd_steinberg(&self) -> Buffer<BitColor> {
+        let (height, width) = self.array.dim();
+
+        let mut working = self
+            .array
+            .map(|c| [c.r.into_inner(), c.g.into_inner(), c.b.into_inner()]);
+
+        let mut result = Buffer::new(Array2::from_elem((height, width), BitColor::Black));
+
+        for y in 0..height {
+            for x in 0..width {
+                let old = working[[y, x]];
+                let new = old.map(|v| v >= 0.5);
+
+                result.array[[y, x]] = BitColor::from_components(new);
+
+                let error = [0, 1, 2].map(|i| old[i] - if new[i] { 1.0 } else { 0.0 });
+
+                let mut diffuse = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+
+                    if nx >= 0 && (nx as usize) < width && y + dy < height {
+                        let cell = &mut working[[y + dy, nx as usize]];
+
+                        for i in 0..3 {
+                            cell[i] += error[i] * weight;
+                        }
+                    }
+                };
+
+                diffuse(1, 0, 7.0 / 16.0);
+                diffuse(-1, 1, 3.0 / 16.0);
+                diffuse(0, 1, 5.0 / 16.0);
+                diffuse(1, 1, 1.0 / 16.0);
+            }
+        }
+
+        result
+    }
+
+    /// Renders luminance as text, width characters wide, averaging the cells under each character.
+    /// Rows are halved to roughly keep the aspect ratio, as terminal characters are about twice as
+    /// tall as they are wide.
+    pub fn to_ascii(&self, width: usize) -> String {
+        const RAMP: &[u8] = b" .:-=+*#%@";
+
+        let (buffer_height, buffer_width) = self.array.dim();
+        let width = width.clamp(1, buffer_width);
+        let height = ((buffer_height * width) as f32 / buffer_width as f32 / 2.0)
+            .round()
+            .clamp(1.0, buffer_height as f32) as usize;
+
+        let mut ascii = String::with_capacity((width + 1) * height);
+
+        for row in 0..height {
+            let ys = row * buffer_height / height..(row + 1) * buffer_height / height;
+
+            for column in 0..width {
+                let xs = column * buffer_width / width..(column + 1) * buffer_width / width;
+
+                let region = self.array.slice(s![ys.clone(), xs]);
+                let luminance = region
+                    .iter()
+                    .map(|c| c.get_luminance().into_inner())
+                    .sum::<f32>()
+                    / region.len() as f32;
+
+                let index = (luminance * (RAMP.len() - 1) as f32).round() as usize;
+                ascii.push(char::from(RAMP[index.min(RAMP.len() - 1)]));
+            }
+
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// Samples the red and blue channels from points pushed radially outward and inward from center,
+    /// by up to a tenth of their distance from it at full amount. Samples past the edges are clamped.
+    pub fn chromatic_aberration(&self, center: SNPoint, amount: UNFloat) -> Self {
+        const MAX_SHIFT: f32 = 0.1;
+
+        let shift = amount.into_inner() * MAX_SHIFT;
+        let center = center.into_inner();
+        let radial = |p: Point2<f32>, scale: f32| {
+            SNPoint::new_normalised(center + (p - center) * scale, SFloatNormaliser::Clamp)
+        };
+
+        let (width, height) = (self.width(), self.height());
+
+        Self::new(Array2::from_shape_fn((height, width), |(y, x)| {
+            let p = cell_center(x, y, width, height).into_inner();
+
+            let red = self.sample_bilinear(radial(p, 1.0 + shift));
+            let green = self[Point2::new(x, y)];
+            let blue = self.sample_bilinear(radial(p, 1.0 - shift));
+
+            FloatColor {
+                r: red.r,
+                g: green.g,
+                b: blue.b,
+                a: green.a,
+            }
+        }))
+    }
+
+    /// Median cut quantization. Repeatedly splits the bucket of pixels with the widest spread in
+    /// any of r, g or b at its median along that channel, until there are n buckets or no bucket
+    /// holds more than one distinct colour, then returns each bucket's average colour.
+    pub fn extract_palette(&self, n: usize) -> Vec<FloatColor> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let channels = |c: &FloatColor| [c.r.into_inner(), c.g.into_inner(), c.b.into_inner()];
+
+        // The channel with the widest range in a bucket, and that range
+        let widest = |bucket: &[FloatColor]| {
+            (0..3)
+                .map(|i| {
+                    let values = bucket.iter().map(|c| channels(c)[i]);
+                    let min = values.clone().fold(f32::INFINITY, f32::min);
+                    let max = values.fold(f32::NEG_INFINITY, f32::max);
+
+                    (i, max - min)
+                })
+                .fold(
+                    (0, 0.0),
+                    |best, next| if next.1 > best.1 { next } else { best },
+                )
+        };
+
+        let mut buckets: Vec<Vec<FloatColor>> = vec![self.array.iter().copied().collect()];
+
+        while buckets.len() < n {
+            let (index, (channel, range)) = buckets
+                .iter()
+                .map(|bucket| widest(bucket))
+                .enumerate()
+                .max_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+                .unwrap();
+
+            if range <= 0.0 {
+                break;
+            }
+
+            let mut bucket = buckets.swap_remove(index);
+            bucket.sort_by(|a, b| channels(a)[channel].total_cmp(&channels(b)[channel]));
+
+            // Split at the median, moved off any run of equal values so both halves are non-empty
+            let median = channels(&bucket[bucket.len() / 2])[channel];
+            let mut split = bucket.partition_point(|c| channels(c)[channel] < median);
+
+            if split == 0 {
+                split = bucket.partition_point(|c| channels(c)[channel] <= median);
+            }
+
+            let upper = bucket.split_off(split);
+            buckets.push(bucket);
+            buckets.push(upper);
+        }
+
+        buckets
+            .iter()
+            .map(|bucket| {
+                let total = bucket.len() as f32;
+                let sum = bucket.iter().fold([0.0; 4], |sum, c| {
+                    [
+                        sum[0] + c.r.into_inner(),
+                        sum[1] + c.g.into_inner(),
+                        sum[2] + c.b.into_inner(),
+                        sum[3] + c.a.into_inner(),
+                    ]
+                });
+
+                FloatColor {
+                    r: UNFloat::new_clamped(sum[0] / total),
+                    g: UNFloat::new_clamped(sum[1] / total),
+                    b: UNFloat::new_clamped(sum[2] / total),
+                    a: UNFloat::new_clamped(sum[3] / total),
+                }
+            })
+            .collect()
+    }
+
+    /// Lens-style warp around center. Each pixel samples from its offset scaled by 1 - k * r^2,
+    /// so positive k stretches the edges outward (pincushion) and negative k pulls them in (barrel).
+    /// Samples past the edges are clamped.
+    pub fn radial_distort(&self, center: SNPoint, k: SNFloat) -> Self {
+        let k = k.into_inner();
+        let center = center.into_inner();
+        let (width, height) = (self.width(), self.height());
+
+        Self::new(Array2::from_shape_fn((height, width), |(y, x)| {
+            let offset = cell_center(x, y, width, height).into_inner() - center;
+            let scale = (1.0 - k * offset.norm_squared()).max(0.0);
+
+            self.sample_bilinear(SNPoint::new_normalised(
+                center + offset * scale,
+                SFloatNormaliser::Clamp,
+            ))
+        }))
+    }
+
+    /// Interpolates between the four cells whose centers surround coords. Past the outermost
+    /// cell centers, the edge cells are extended.
+    pub fn sample_bilinear(&self, coords: SNPoint) -> FloatColor {
+        let (width, height) = (self.width(), self.height());
+
+        // Continuous cell coordinates, snapped when within rounding error of a cell center
+        let to_cell = |v: SNFloat, len: usize| {
+            let c = (v.to_unsigned().into_inner() * len as f32 - 0.5).clamp(0.0, (len - 1) as f32);
+
+            if (c - c.round()).abs() < 1e-4 {
+                c.round()
+            } else {
+                c
+            }
+        };
+
+        let fx = to_cell(coords.x(), width);
+        let fy = to_cell(coords.y(), height);
+
+        let (x0, y0) = (fx.floor() as usize, fy.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let tx = UNFloat::new_clamped(fx - x0 as f32);
+        let ty = UNFloat::new_clamped(fy - y0 as f32);
+
+        let top = self.array[[y0, x0]].lerp(self.array[[y0, x1]], tx);
+        let bottom = self.array[[y1, x0]].lerp(self.array[[y1, x1]], tx);
+
+        top.lerp(bottom, ty)
+    }
+
+    /// Composites a soft disc of color over the buffer, its opacity falling off as a Gaussian
+    /// from full at center to nothing beyond radius
+    pub fn splat(&mut self, center: SNPoint, radius: UNFloat, color: FloatColor) {
+        let radius = radius.into_inner();
+        let sigma = radius / 3.0;
+
+        for (p, pixel) in self.iter_points_mut() {
+            let d = distance(&p.into_inner(), &center.into_inner());
+
+            if d >= radius {
+                continue;
+            }
+
+            let weight = (-(d * d) / (2.0 * sigma * sigma)).exp();
+
+            *pixel = FloatColor {
+                a: color.a.multiply(UNFloat::new_clamped(weight)),
+                ..color
+            }
+            .over(*pixel);
+        }
+    }
+
+    /// Draws a line by blending color onto each pixel it crosses, with the pixel as the base layer
+    pub fn draw_line_blended(
+        &mut self,
+        from: SNPoint,
+        to: SNPoint,
+        color: FloatColor,
+        blend: ColorBlendFunctions,
+    ) {
+        self.draw_line_with(from, to, |pixel| blend.blend(*pixel, color));
+    }
+}
+
+impl Buffer<UNFloat> {
+    /// Histogram equalization: remaps each value to the fraction of values at or below it,
+    /// rescaled so the smallest value maps to zero. A uniform or empty buffer is returned unchanged.
+    pub fn equalize(&self) -> Self {
+        let mut sorted: Vec<f32> = self.array.iter().map(|v| v.into_inner()).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        if sorted.is_empty() {
+            return Self::new(self.array.clone());
+        }
+
+        let total = sorted.len() as f32;
+        let cdf = |v: f32| sorted.partition_point(|&x| x <= v) as f32 / total;
+        let cdf_min = cdf(sorted[0]);
+
+        if cdf_min >= 1.0 {
+            return Self::new(self.array.clone());
+        }
+
+        Self::new(
+            self.array
+                .map(|v| UNFloat::new_clamped((cdf(v.into_inner()) - cdf_min) / (1.0 - cdf_min))),
+        )
+    }
+}
+
+/// Blends a stack of layers from the bottom up, each with its own blend mode and opacity.
+/// The bottom layer is the starting image, so its mode and opacity are unused.
+/// Panics if there are no layers or their dimensions differ.
+pub fn composite(
+    layers: &[(Buffer<FloatColor>, ColorBlendFunctions, UNFloat)],
+) -> Buffer<FloatColor> {
+    let (bottom, ..) = layers
+        .first()
+        .expect("Cannot composite an empty layer stack");
+    let mut result = bottom.array.clone();
+
+    for (layer, blend, opacity) in &layers[1..] {
+        assert_eq!(
+            layer.array.dim(),
+            result.dim(),
+            "Cannot composite layers of differing dimensions"
+        );
+
+        Zip::from(&mut result)
+            .and(&layer.array)
+            .for_each(|below, &above| {
+                *below = below.lerp(blend.blend(*below, above), *opacity);
+            });
+    }
+
+    Buffer::new(result)
+}
+
+/// Thresholds a luminance signal, e.g. from Buffer::row_luminance, into cells for an ElementaryAutomataRule
+pub fn booleans_from_luminance(row: &[UNFloat], threshold: UNFloat) -> Vec<Boolean> {
+    row.iter()
+        .map(|l| Boolean::new(l.into_inner() >= threshold.into_inner()))
+        .collect()
 }
 
 impl<T> Index<SNPoint> for Buffer<T> {
@@ -129,11 +927,111 @@ where
     }
 }
 
-impl<'a, T: Default> Default for Buffer<T> {
-    fn default() -> Self {
-        Self::new(Array2::from_shape_fn((255, 255), |(_y, _x)| T::default()))
-    }
-}
+impl<T: Serialize + PartialEq> Buffer<T> {
+    /// Serializes the contents as well as the dimensions, run-length encoded.
+    /// The plain Serialize impl only stores dimensions.
+    pub fn serialize_full<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut runs: Vec<(usize, &T)> = Vec::new();
+
+        for value in self.array.iter() {
+            match runs.last_mut() {
+                Some((count, last)) if *last == value => *count += 1,
+                _ => runs.push((1, value)),
+            }
+        }
+
+        BufferDataRef {
+            info: self.info(),
+            runs,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<T: Clone> Buffer<T> {
+    /// Restores a buffer written by Buffer::serialize_full
+    pub fn deserialize_full<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let data = BufferDataOwned::<T>::deserialize(deserializer)?;
+        let BufferInfo {
+            width,
+            height,
+            params,
+        } = data.info;
+
+        let total = width.checked_mul(height).ok_or_else(|| {
+            de::Error::custom(format!("Buffer dimensions {}x{} overflow", width, height))
+        })?;
+
+        // Checked before expanding the runs, so bad counts can't force a huge allocation
+        let run_total = data
+            .runs
+            .iter()
+            .try_fold(0usize, |sum, (count, _)| sum.checked_add(*count));
+
+        if run_total != Some(total) {
+            return Err(de::Error::custom(format!(
+                "Buffer data runs don't add up to {}x{}",
+                width, height
+            )));
+        }
+
+        let mut values: Vec<T> = Vec::with_capacity(total);
+
+        for (count, value) in data.runs {
+            values.resize(values.len() + count, value);
+        }
+
+        let array = Array2::from_shape_vec((height, width), values).map_err(de::Error::custom)?;
+
+        Ok(Self::with_params(array, params))
+    }
+}
+
+/// Buffer wrapper that serializes its full contents rather than just its dimensions
+pub struct BufferData<T>(pub Buffer<T>);
+
+impl<T: Serialize + PartialEq> Serialize for BufferData<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_full(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Clone> Deserialize<'de> for BufferData<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Buffer::deserialize_full(deserializer).map(BufferData)
+    }
+}
+
+#[derive(Serialize)]
+struct BufferDataRef<'a, T> {
+    info: BufferInfo,
+    runs: Vec<(usize, &'a T)>,
+}
+
+#[derive(Deserialize)]
+struct BufferDataOwned<T> {
+    info: BufferInfo,
+    runs: Vec<(usize, T)>,
+}
+
+impl<'a, T: Default> Default for Buffer<T> {
+    fn default() -> Self {
+        Self::new(Array2::from_shape_fn((255, 255), |(_y, _x)| T::default()))
+    }
+}
 
 impl<'a, T> Generatable<'a> for Buffer<T>
 where
@@ -142,7 +1040,9 @@ where
     type GenArg = ProtoGenArg<'a>;
 
     fn generate_rng<R: Rng + ?Sized>(rng: &mut R, mut arg: Self::GenArg) -> Self {
-        Self::new(Array2::from_shape_fn(
+        let params = BufferGenParams::random(rng);
+
+        let array = Array2::from_shape_fn(
             (
                 Byte::generate_rng(rng, arg.reborrow()).into_inner() as usize + 1,
                 Byte::generate_rng(rng, arg.reborrow()).into_inner() as usize + 1,
@@ -151,18 +1051,32 @@ where
                 let a: ProtoGenArg<'_> = ProtoGenArg::<'a>::reborrow(&mut arg);
                 T::generate_rng(rng, a)
             },
-        ))
+        );
+
+        Self::with_params(array, params)
     }
 }
 
-impl<'a, T: Mutatable<'a>> Mutatable<'a> for Buffer<T> {
+impl<'a, T: Mutatable<'a> + Clone> Mutatable<'a> for Buffer<T> {
     type MutArg = T::MutArg;
 
-    fn mutate_rng<R: Rng + ?Sized>(&mut self, _rng: &mut R, _arg: Self::MutArg) {
-        //TODO: find a way to mutate this that doesn't look like a rainbow static explosion
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: Self::MutArg) {
+        //TODO: find a way to mutate the contents that doesn't look like a rainbow static explosion
         // for inner in self.array.iter_mut() {
         //     inner.mutate_rng(rng, state, arg.clone());
         // }
+
+        if self.params.nudge_dimensions {
+            let nudge = |len: usize, rng: &mut R| {
+                (len as isize + rng.gen_range(-1..=1)).clamp(1, BufferGenParams::MAX_SIZE as isize)
+                    as usize
+            };
+
+            let height = nudge(self.height(), rng);
+            let width = nudge(self.width(), rng);
+
+            self.resize(width, height);
+        }
     }
 }
 
@@ -176,10 +1090,29 @@ impl<'a, T: UpdatableRecursively<'a>> UpdatableRecursively<'a> for Buffer<T> {
     fn update_recursively(&mut self, _arg: Self::UpdateArg) {}
 }
 
+/// Generation parameters kept alongside a buffer so mutation can preserve its structure
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferGenParams {
+    /// Whether mutation may grow or shrink each dimension by one cell, rather than keeping it fixed
+    pub nudge_dimensions: bool,
+}
+
+impl BufferGenParams {
+    pub const MAX_SIZE: usize = 256;
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            nudge_dimensions: rng.gen(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct BufferInfo {
     width: usize,
     height: usize,
+    #[serde(default)]
+    params: BufferGenParams,
 }
 
 impl BufferInfo {
@@ -187,7 +1120,7 @@ impl BufferInfo {
     where
         T: Default,
     {
-        Buffer::new(Array2::default([self.height, self.width]))
+        Buffer::with_params(Array2::default([self.height, self.width]), self.params)
     }
 }
 
@@ -197,6 +1130,354 @@ mod test {
 
     use ndarray::array;
 
+    #[test]
+    fn palette_cycling_tests() {
+        let palette: [ByteColor; 8] = std::array::from_fn(|i| ByteColor {
+            r: Byte::new(i as u8 * 30),
+            g: Byte::new(255 - i as u8 * 30),
+            b: Byte::new(i as u8),
+            a: Byte::new(255),
+        });
+
+        let buffer = Buffer::new(Array2::from_shape_fn((2, 4), |(y, x)| {
+            BitColor::from_index(y * 4 + x)
+        }));
+
+        let mut shifted = palette;
+        cycle_palette(&mut shifted, 3);
+
+        let original = buffer.to_float_buffer(&palette);
+        let cycled = buffer.to_float_buffer(&shifted);
+
+        for (&color, &c) in buffer.array.iter().zip(cycled.array.iter()) {
+            let i = color.to_index();
+
+            assert_eq!(c, FloatColor::from(palette[(i + 8 - 3) % 8]));
+            assert_eq!(
+                c,
+                original.array.iter().nth((i + 8 - 3) % 8).copied().unwrap()
+            );
+        }
+
+        cycle_palette(&mut shifted, 5);
+        assert_eq!(shifted, palette);
+    }
+
+    #[test]
+    fn equalize_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let clustered = Buffer::new(Array2::from_shape_fn((32, 32), |_| {
+            UNFloat::new(rng.gen_range(0.4..0.6))
+        }));
+
+        let equalized = clustered.equalize();
+        let values: Vec<f32> = equalized.array.iter().map(|v| v.into_inner()).collect();
+
+        assert_eq!(values.iter().cloned().fold(f32::MAX, f32::min), 0.0);
+        assert_eq!(values.iter().cloned().fold(f32::MIN, f32::max), 1.0);
+
+        // Roughly uniform, so each quarter of the range holds about a quarter of the cells
+        for quarter in 0..4 {
+            let range = quarter as f32 * 0.25..(quarter + 1) as f32 * 0.25;
+            let count = values.iter().filter(|v| range.contains(v)).count();
+
+            assert!((count as f32 / values.len() as f32 - 0.25).abs() < 0.02);
+        }
+
+        // Ordering is preserved
+        for (a, b) in clustered.array.iter().zip(equalized.array.iter()).take(100) {
+            for (c, d) in clustered.array.iter().zip(equalized.array.iter()).take(100) {
+                if a.into_inner() < c.into_inner() {
+                    assert!(b.into_inner() <= d.into_inner());
+                }
+            }
+        }
+
+        let flat = Buffer::new(Array2::from_elem((4, 4), UNFloat::new(0.3)));
+        assert_eq!(flat.equalize().array, flat.array);
+
+        let empty = Buffer::new(Array2::<UNFloat>::from_elem((0, 0), UNFloat::ZERO));
+        assert_eq!(empty.equalize().array.dim(), (0, 0));
+    }
+
+    #[test]
+    fn morphology_tests() {
+        let mut single = Buffer::new(Array2::from_elem((5, 5), BitColor::Black));
+        single[Point2::new(2, 2)] = BitColor::White;
+
+        let dilated = single.dilate(&PixelNeighbourhood::Moore);
+
+        for ((y, x), &color) in dilated.array.indexed_iter() {
+            let near = (1..=3).contains(&x) && (1..=3).contains(&y);
+            assert_eq!(
+                color,
+                if near {
+                    BitColor::White
+                } else {
+                    BitColor::Black
+                }
+            );
+        }
+
+        assert_eq!(
+            dilated.erode(&PixelNeighbourhood::Moore).array,
+            single.array
+        );
+        assert!(single
+            .open(&PixelNeighbourhood::Moore)
+            .array
+            .iter()
+            .all(|&c| c == BitColor::Black));
+
+        // Wraps around the edges
+        let mut corner = Buffer::new(Array2::from_elem((5, 5), BitColor::Black));
+        corner[Point2::new(0, 0)] = BitColor::Red;
+
+        let dilated = corner.dilate(&PixelNeighbourhood::VonNeumann);
+        assert_eq!(dilated[Point2::new(4, 0)], BitColor::Red);
+        assert_eq!(dilated[Point2::new(0, 4)], BitColor::Red);
+        assert_eq!(dilated[Point2::new(4, 4)], BitColor::Black);
+    }
+
+    #[test]
+    fn floyd_steinberg_tests() {
+        let (height, width) = (32, 64);
+        let gradient = Buffer::new(Array2::from_shape_fn((height, width), |(_, x)| {
+            let v = UNFloat::new(x as f32 / (width - 1) as f32);
+
+            FloatColor {
+                r: v,
+                g: v,
+                b: v,
+                a: UNFloat::ONE,
+            }
+        }));
+
+        let dithered = gradient.to_bitcolor_floyd_steinberg();
+
+        assert!(dithered
+            .array
+            .iter()
+            .all(|&c| c == BitColor::Black || c == BitColor::White));
+        assert!(dithered.array.iter().any(|&c| c == BitColor::Black));
+        assert!(dithered.array.iter().any(|&c| c == BitColor::White));
+
+        let white_fraction = |cells: &mut dyn Iterator<Item = &BitColor>| {
+            let (white, total) = cells.fold((0, 0), |(white, total), &c| {
+                (white + (c == BitColor::White) as usize, total + 1)
+            });
+
+            white as f32 / total as f32
+        };
+
+        assert!((white_fraction(&mut dithered.array.iter()) - 0.5).abs() < 0.02);
+
+        // Each eighth of the gradient averages out to roughly its input level
+        for band in 0..8 {
+            let columns = band * width / 8..(band + 1) * width / 8;
+            let expected = (columns.start + columns.end - 1) as f32 / 2.0 / (width - 1) as f32;
+
+            let actual = white_fraction(&mut dithered.array.slice(s![.., columns]).iter());
+            assert!(
+                (actual - expected).abs() < 0.1,
+                "band {}: {} vs {}",
+                band,
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn to_ascii_tests() {
+        let white = Buffer::new(Array2::from_elem((32, 64), FloatColor::WHITE));
+        let black = Buffer::new(Array2::from_elem((32, 64), FloatColor::BLACK));
+
+        let white_ascii = white.to_ascii(16);
+        let black_ascii = black.to_ascii(16);
+
+        assert_eq!(white_ascii.lines().count(), 4);
+        assert!(white_ascii.lines().all(|line| line == "@".repeat(16)));
+        assert!(black_ascii.lines().all(|line| line == " ".repeat(16)));
+    }
+
+    #[test]
+    fn chromatic_aberration_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let buffer = Buffer::new(Array2::from_shape_fn((12, 20), |_| {
+            FloatColor::random(&mut rng)
+        }));
+
+        let unchanged = buffer.chromatic_aberration(SNPoint::random(&mut rng), UNFloat::ZERO);
+        assert_eq!(unchanged.array, buffer.array);
+
+        let shifted = buffer.chromatic_aberration(SNPoint::zero(), UNFloat::ONE);
+        assert_eq!(shifted.array.dim(), buffer.array.dim());
+        assert_ne!(shifted.array, buffer.array);
+    }
+
+    #[test]
+    fn radial_distort_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let buffer = Buffer::new(Array2::from_shape_fn((12, 20), |_| {
+            FloatColor::random(&mut rng)
+        }));
+
+        let unchanged = buffer.radial_distort(SNPoint::random(&mut rng), SNFloat::ZERO);
+        assert_eq!(unchanged.array, buffer.array);
+
+        for k in [-1.0, 1.0] {
+            let distorted = buffer.radial_distort(SNPoint::zero(), SNFloat::new(k));
+            assert_eq!(distorted.array.dim(), buffer.array.dim());
+            assert_ne!(distorted.array, buffer.array);
+        }
+
+        // Pincushion stretches the image outward, so a cell right of center shows what was
+        // nearer the center. Barrel pulls the image in, so it shows what was further out.
+        let gradient = Buffer::new(Array2::from_shape_fn((16, 16), |(_, x)| FloatColor {
+            r: UNFloat::new(x as f32 / 15.0),
+            ..FloatColor::BLACK
+        }));
+        let cell = Point2::new(12, 7);
+        let red_at = |k: f32| {
+            gradient.radial_distort(SNPoint::zero(), SNFloat::new(k))[cell]
+                .r
+                .into_inner()
+        };
+
+        assert!(red_at(0.5) < gradient[cell].r.into_inner());
+        assert!(red_at(-0.5) > gradient[cell].r.into_inner());
+    }
+
+    #[test]
+    fn sample_bilinear_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let buffer = Buffer::new(Array2::from_shape_fn((3, 4), |_| {
+            FloatColor::random(&mut rng)
+        }));
+
+        let close = |a: FloatColor, b: FloatColor| {
+            [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)]
+                .iter()
+                .all(|(a, b)| a.approx_eq(*b, 1e-5))
+        };
+
+        let midpoint =
+            |a: SNPoint, b: SNPoint| SNPoint::new((a.into_inner() + b.into_inner().coords) * 0.5);
+
+        for y in 0..3 {
+            for x in 0..4 {
+                let cell = buffer[Point2::new(x, y)];
+                let center = buffer.cell_center(x, y);
+
+                assert!(close(buffer.sample_bilinear(center), cell));
+
+                if x + 1 < 4 {
+                    let between = midpoint(center, buffer.cell_center(x + 1, y));
+                    let average = cell.lerp(buffer[Point2::new(x + 1, y)], UNFloat::new(0.5));
+                    assert!(close(buffer.sample_bilinear(between), average));
+                }
+
+                if y + 1 < 3 {
+                    let between = midpoint(center, buffer.cell_center(x, y + 1));
+                    let average = cell.lerp(buffer[Point2::new(x, y + 1)], UNFloat::new(0.5));
+                    assert!(close(buffer.sample_bilinear(between), average));
+                }
+            }
+        }
+
+        let corner = SNPoint::new(Point2::new(-1.0, -1.0));
+        assert!(close(
+            buffer.sample_bilinear(corner),
+            buffer[Point2::new(0, 0)]
+        ));
+    }
+
+    #[test]
+    fn offset_wrapped_tests() {
+        let buffer = Buffer::new(Array2::from_shape_fn((3, 5), |(y, x)| y * 10 + x));
+
+        assert_eq!(buffer.offset_wrapped(5, 0).array, buffer.array);
+        assert_eq!(buffer.offset_wrapped(-5, 3).array, buffer.array);
+        assert_eq!(buffer.offset_wrapped(0, 0).array, buffer.array);
+
+        let shifted = buffer.offset_wrapped(1, 0);
+        assert_eq!(shifted.row(0), vec![4, 0, 1, 2, 3]);
+        assert_eq!(shifted.row(2), vec![24, 20, 21, 22, 23]);
+
+        let shifted = buffer.offset_wrapped(-1, -1);
+        assert_eq!(shifted.row(0), vec![11, 12, 13, 14, 10]);
+        assert_eq!(shifted.column(0), vec![11, 21, 1]);
+    }
+
+    #[test]
+    fn extract_palette_tests() {
+        let orange = FloatColor {
+            r: UNFloat::new(0.9),
+            g: UNFloat::new(0.6),
+            b: UNFloat::new(0.1),
+            a: UNFloat::ONE,
+        };
+        let teal = FloatColor {
+            r: UNFloat::new(0.1),
+            g: UNFloat::new(0.5),
+            b: UNFloat::new(0.5),
+            a: UNFloat::ONE,
+        };
+
+        let buffer = Buffer::new(Array2::from_shape_fn((9, 7), |(y, x)| {
+            if (x * 3 + y) % 5 < 2 {
+                orange
+            } else {
+                teal
+            }
+        }));
+
+        let mut palette = buffer.extract_palette(2);
+        palette.sort_by(|a, b| a.r.partial_cmp(&b.r).unwrap());
+
+        let close = |a: FloatColor, b: FloatColor| {
+            a.r.approx_eq(b.r, 1e-5)
+                && a.g.approx_eq(b.g, 1e-5)
+                && a.b.approx_eq(b.b, 1e-5)
+                && a.a.approx_eq(b.a, 1e-5)
+        };
+
+        assert_eq!(palette.len(), 2);
+        assert!(close(palette[0], teal));
+        assert!(close(palette[1], orange));
+
+        // Never more colours than the buffer holds, and nothing for n = 0
+        assert_eq!(buffer.extract_palette(5).len(), 2);
+        assert!(buffer.extract_palette(0).is_empty());
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let noise = Buffer::new(Array2::from_shape_fn((16, 16), |_| {
+            FloatColor::random(&mut rng)
+        }));
+        assert_eq!(noise.extract_palette(8).len(), 8);
+        assert_eq!(noise.extract_palette(8), noise.extract_palette(8));
+    }
+
+    #[test]
+    fn splat_tests() {
+        let mut buffer = Buffer::new(Array2::from_elem((16, 16), FloatColor::BLACK));
+        let center = buffer.cell_center(8, 8);
+        let color = FloatColor {
+            r: UNFloat::ONE,
+            g: UNFloat::new(0.5),
+            b: UNFloat::ZERO,
+            a: UNFloat::ONE,
+        };
+
+        buffer.splat(center, UNFloat::new(0.25), color);
+
+        assert_eq!(buffer[Point2::new(8, 8)], color);
+        assert_ne!(buffer[Point2::new(9, 8)], FloatColor::BLACK);
+        assert_eq!(buffer[Point2::new(11, 8)], FloatColor::BLACK);
+        assert_eq!(buffer[Point2::new(0, 0)], FloatColor::BLACK);
+    }
+
     #[test]
     fn point_to_uint_tests() {
         let buffer = Buffer::new(Array2::from_elem((100, 100), 0u32));
@@ -213,6 +1494,302 @@ mod test {
         );
     }
 
+    #[test]
+    fn row_column_at_tests() {
+        let buffer = Buffer::new(Array2::from_shape_fn((37, 53), |(y, x)| (x, y)));
+
+        for i in 0..=100 {
+            let f = SNFloat::new(i as f32 / 50.0 - 1.0);
+            let p = buffer.point_to_uint(SNPoint::from_snfloats(f, f));
+
+            assert!(buffer.row_at(f).iter().all(|&(_x, y)| y == p.y));
+            assert!(buffer.column_at(f).iter().all(|&(x, _y)| x == p.x));
+        }
+    }
+
+    #[test]
+    fn row_column_round_trip_tests() {
+        let mut buffer = Buffer::new(Array2::from_shape_fn((4, 6), |(y, x)| y * 6 + x));
+        let original = buffer.array.clone();
+
+        for y in 0..buffer.height() {
+            let row = buffer.row(y);
+            buffer.set_row(y, &row).unwrap();
+        }
+
+        for x in 0..buffer.width() {
+            let column = buffer.column(x);
+            buffer.set_column(x, &column).unwrap();
+        }
+
+        assert_eq!(buffer.array, original);
+
+        buffer.set_row(1, &[0; 6]).unwrap();
+        assert_eq!(buffer.row(1), vec![0; 6]);
+
+        assert!(buffer.set_row(0, &[0; 4]).is_err());
+        assert!(buffer.set_column(0, &[0; 6]).is_err());
+    }
+
+    #[test]
+    fn iter_points_tests() {
+        let mut buffer = Buffer::new(array![[0, 1], [2, 3]]);
+
+        let points: Vec<_> = buffer
+            .iter_points()
+            .map(|(p, &v)| (p.x().into_inner(), p.y().into_inner(), v))
+            .collect();
+
+        assert_eq!(
+            points,
+            vec![
+                (-0.5, -0.5, 0),
+                (0.5, -0.5, 1),
+                (-0.5, 0.5, 2),
+                (0.5, 0.5, 3)
+            ]
+        );
+
+        for (p, v) in buffer.iter_points_mut() {
+            if p.x().into_inner() > 0.0 {
+                *v += 10;
+            }
+        }
+
+        assert_eq!(buffer.array, array![[0, 11], [2, 13]]);
+    }
+
+    #[test]
+    fn iter_points_index_back_to_their_cells() {
+        for height in 1..=9 {
+            for width in 1..=9 {
+                let buffer = Buffer::new(Array2::from_shape_fn((height, width), |(y, x)| (x, y)));
+
+                for (p, v) in buffer.iter_points() {
+                    assert_eq!(buffer[p], *v);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn label_components_tests() {
+        use BitColor::{Black as B, White as W};
+
+        let buffer = Buffer::new(array![
+            [W, W, B, B, B],
+            [W, W, B, B, B],
+            [B, B, B, W, W],
+            [B, B, B, W, W],
+        ]);
+
+        let (labels, count) = buffer.label_components();
+
+        assert_eq!(count, 3);
+        assert_eq!(labels.array[[0, 0]], labels.array[[1, 1]]);
+        assert_eq!(labels.array[[2, 3]], labels.array[[3, 4]]);
+        assert_ne!(labels.array[[0, 0]], labels.array[[3, 4]]);
+        assert_ne!(labels.array[[0, 0]], labels.array[[0, 2]]);
+        assert_ne!(labels.array[[3, 4]], labels.array[[0, 2]]);
+        assert!(labels.array.iter().all(|&l| (l as usize) < count));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn color_statistics_tests() {
+        use BitColor::{Black as K, Blue as B, Red as R, White as W, Yellow as Y};
+
+        let buffer = Buffer::new(array![
+            [K, K, R, R],
+            [K, R, R, W],
+            [K, Y, B, W],
+            [K, K, K, B],
+        ]);
+
+        let histogram = buffer.color_histogram();
+
+        assert_eq!(histogram, [7, 4, 0, 2, 0, 0, 1, 2]);
+        assert_eq!(histogram.iter().sum::<usize>(), 16);
+        assert_eq!(buffer.dominant_color(), BitColor::Black);
+        assert_eq!(buffer.fraction_alive(), UNFloat::new(9.0 / 16.0));
+        assert_eq!(
+            buffer.component_fractions(),
+            [
+                UNFloat::new(7.0 / 16.0),
+                UNFloat::new(3.0 / 16.0),
+                UNFloat::new(4.0 / 16.0),
+            ]
+        );
+
+        let empty = Buffer::new(Array2::from_elem((4, 4), BitColor::Black));
+
+        assert_eq!(empty.fraction_alive(), UNFloat::ZERO);
+        assert_eq!(empty.component_fractions(), [UNFloat::ZERO; 3]);
+    }
+
+    #[test]
+    fn mutate_dimensions_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        let mut fixed = Buffer::new(Array2::from_elem((5, 7), Byte::new(3)));
+
+        for _ in 0..50 {
+            fixed.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+            assert_eq!((fixed.width(), fixed.height()), (7, 5));
+        }
+
+        let mut nudged = Buffer::with_params(
+            Array2::from_elem((5, 7), Byte::new(3)),
+            BufferGenParams {
+                nudge_dimensions: true,
+            },
+        );
+
+        for _ in 0..50 {
+            let (width, height) = (nudged.width(), nudged.height());
+
+            nudged.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+
+            assert!(nudged.width().abs_diff(width) <= 1);
+            assert!(nudged.height().abs_diff(height) <= 1);
+            assert!(nudged.array.iter().all(|b| b.into_inner() == 3));
+        }
+    }
+
+    #[test]
+    fn buffer_data_round_trip_tests() {
+        let mut buffer = Buffer::new(Array2::from_elem((16, 24), BitColor::Black));
+        buffer.draw_line(
+            SNPoint::from_snfloats(SNFloat::new(-0.8), SNFloat::new(-0.5)),
+            SNPoint::from_snfloats(SNFloat::new(0.7), SNFloat::new(0.9)),
+            BitColor::Cyan,
+        );
+
+        let json = serde_json::to_string(&BufferData(buffer)).unwrap();
+        let BufferData(restored) = serde_json::from_str::<BufferData<BitColor>>(&json).unwrap();
+
+        let mut expected = Buffer::new(Array2::from_elem((16, 24), BitColor::Black));
+        expected.draw_line(
+            SNPoint::from_snfloats(SNFloat::new(-0.8), SNFloat::new(-0.5)),
+            SNPoint::from_snfloats(SNFloat::new(0.7), SNFloat::new(0.9)),
+            BitColor::Cyan,
+        );
+
+        assert_eq!(restored.array, expected.array);
+
+        let short = r#"{"info":{"width":2,"height":2},"runs":[[3,"Black"]]}"#;
+        assert!(serde_json::from_str::<BufferData<BitColor>>(short).is_err());
+
+        let long = r#"{"info":{"width":2,"height":2},"runs":[[18446744073709551615,"Black"]]}"#;
+        assert!(serde_json::from_str::<BufferData<BitColor>>(long).is_err());
+
+        let wrapping = r#"{"info":{"width":2,"height":2},"runs":[[18446744073709551615,"Black"],[5,"White"]]}"#;
+        assert!(serde_json::from_str::<BufferData<BitColor>>(wrapping).is_err());
+
+        let huge = r#"{"info":{"width":18446744073709551615,"height":2},"runs":[]}"#;
+        assert!(serde_json::from_str::<BufferData<BitColor>>(huge).is_err());
+    }
+
+    #[test]
+    fn composite_tests() {
+        let gray = |v: f32| FloatColor {
+            r: UNFloat::new(v),
+            g: UNFloat::new(v),
+            b: UNFloat::new(v),
+            a: UNFloat::ONE,
+        };
+
+        let layers = [
+            (
+                Buffer::new(Array2::from_elem((3, 5), gray(0.2))),
+                ColorBlendFunctions::ScreenDodge,
+                UNFloat::ONE,
+            ),
+            (
+                Buffer::new(Array2::from_elem((3, 5), gray(0.4))),
+                ColorBlendFunctions::ScreenDodge,
+                UNFloat::new(0.5),
+            ),
+        ];
+
+        let result = composite(&layers);
+
+        assert_eq!((result.width(), result.height()), (5, 3));
+
+        for c in result.array.iter() {
+            assert!((c.r.into_inner() - 0.36).abs() < 1e-5);
+            assert_eq!(c.r, c.g);
+            assert_eq!(c.r, c.b);
+            assert_eq!(c.a, UNFloat::ONE);
+        }
+    }
+
+    #[test]
+    fn threshold_tests() {
+        let gradient = Buffer::new(Array2::from_shape_fn((1, 8), |(_, x)| {
+            let v = UNFloat::new(x as f32 / 7.0);
+
+            FloatColor {
+                r: v,
+                g: v,
+                b: v,
+                a: UNFloat::ONE,
+            }
+        }));
+
+        let thresholded =
+            gradient.threshold(UNFloat::new(0.5), FloatColor::BLACK, FloatColor::WHITE);
+
+        assert_eq!(thresholded.row(0)[..4], [FloatColor::BLACK; 4]);
+        assert_eq!(thresholded.row(0)[4..], [FloatColor::WHITE; 4]);
+    }
+
+    #[test]
+    fn posterize_tests() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let buffer = Buffer::new(Array2::from_shape_fn((8, 8), |_| {
+            FloatColor::random(&mut rng)
+        }));
+
+        let posterized = buffer.posterize(Nibble::new(1));
+
+        for c in posterized.array.iter() {
+            for channel in [c.r, c.g, c.b] {
+                assert!(channel.into_inner() == 0.0 || channel.into_inner() == 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn posterize_zero_levels_tests() {
+        let buffer = Buffer::new(array![[FloatColor::WHITE, FloatColor::BLACK]]);
+
+        let posterized = buffer.posterize(Nibble::new(0));
+
+        assert_eq!(
+            posterized.array,
+            array![[FloatColor::BLACK, FloatColor::BLACK]]
+        );
+    }
+
+    #[test]
+    fn booleans_from_luminance_tests() {
+        let buffer = Buffer::new(array![[
+            FloatColor::BLACK,
+            FloatColor::WHITE,
+            FloatColor::BLACK
+        ]]);
+
+        let booleans: Vec<bool> =
+            booleans_from_luminance(&buffer.row_luminance(0), UNFloat::new(0.5))
+                .into_iter()
+                .map(Boolean::into_inner)
+                .collect();
+
+        assert_eq!(booleans, vec![false, true, false]);
+    }
+
     #[test]
     #[rustfmt::skip]
     fn draw_line_tests() {
@@ -272,6 +1849,215 @@ mod test {
         );
     }
 
+    #[test]
+    fn draw_line_with_tests() {
+        let add_red = |pixel: &FloatColor| FloatColor {
+            r: UNFloat::new_clamped(pixel.r.into_inner() + 0.25),
+            ..*pixel
+        };
+
+        let corners = [(-1.0, -1.0), (1.0, 1.0), (-1.0, 1.0), (1.0, -1.0)]
+            .map(|(x, y)| SNPoint::new(Point2::new(x, y)));
+
+        let mut buffer = Buffer::new(Array2::from_elem((5, 5), FloatColor::BLACK));
+        buffer.draw_line_with(corners[0], corners[1], add_red);
+        buffer.draw_line_with(corners[2], corners[3], add_red);
+
+        let reds = buffer.array.map(|c| c.r.into_inner());
+        let expected = Array2::from_shape_fn((5, 5), |(y, x)| match (x == y, x + y == 4) {
+            (true, true) => 0.5,
+            (true, false) | (false, true) => 0.25,
+            (false, false) => 0.0,
+        });
+        assert_eq!(reds, expected);
+
+        buffer.draw_dot_with(corners[0], add_red);
+        assert_eq!(buffer[Point2::new(0, 0)].r.into_inner(), 0.5);
+
+        let mut blended = Buffer::new(Array2::from_elem((5, 5), FloatColor::BLACK));
+        let half_red = FloatColor {
+            r: UNFloat::new(0.5),
+            ..FloatColor::BLACK
+        };
+        blended.draw_line_blended(
+            corners[0],
+            corners[1],
+            half_red,
+            ColorBlendFunctions::ScreenDodge,
+        );
+        blended.draw_line_blended(
+            corners[2],
+            corners[3],
+            half_red,
+            ColorBlendFunctions::ScreenDodge,
+        );
+
+        assert_eq!(blended[Point2::new(2, 2)].r.into_inner(), 0.75);
+        assert_eq!(blended[Point2::new(0, 0)].r.into_inner(), 0.5);
+        assert_eq!(blended[Point2::new(1, 0)].r.into_inner(), 0.0);
+    }
+
+    #[test]
+    fn draw_circle_tests() {
+        test_draw_circle(
+            (0.0, 0.0),
+            0.5,
+            array![[0, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 1], [0, 0, 1, 0],],
+        );
+
+        test_draw_circle(
+            (1.0, 1.0),
+            0.5,
+            array![[0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 1], [0, 0, 1, 0],],
+        );
+
+        test_draw_circle(
+            (-1.0, -1.0),
+            0.0,
+            array![[1, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],],
+        );
+
+        test_draw_circle(
+            (-0.2, -0.2),
+            0.8,
+            array![
+                [0, 1, 1, 1, 0],
+                [1, 0, 0, 0, 1],
+                [1, 0, 0, 0, 1],
+                [1, 0, 0, 0, 1],
+                [0, 1, 1, 1, 0],
+            ],
+        );
+
+        // A non-square buffer scales the radius per axis, so the circle becomes an ellipse
+        test_draw_circle(
+            (0.0, 0.0),
+            0.5,
+            array![
+                [0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 1, 1, 1, 0, 0],
+                [0, 0, 1, 0, 0, 0, 1, 0],
+                [0, 0, 0, 1, 1, 1, 0, 0],
+            ],
+        );
+    }
+
+    #[test]
+    fn draw_filled_circle_tests() {
+        test_draw_filled_circle(
+            (0.0, 0.0),
+            0.5,
+            array![[0, 0, 0, 0], [0, 0, 1, 0], [0, 1, 1, 1], [0, 0, 1, 0],],
+        );
+
+        test_draw_filled_circle(
+            (1.0, 1.0),
+            0.5,
+            array![[0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 1], [0, 0, 1, 1],],
+        );
+
+        test_draw_filled_circle(
+            (-1.0, -1.0),
+            0.0,
+            array![[1, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],],
+        );
+
+        test_draw_filled_circle(
+            (-0.2, -0.2),
+            0.8,
+            array![
+                [0, 1, 1, 1, 0],
+                [1, 1, 1, 1, 1],
+                [1, 1, 1, 1, 1],
+                [1, 1, 1, 1, 1],
+                [0, 1, 1, 1, 0],
+            ],
+        );
+
+        test_draw_filled_circle(
+            (0.0, 0.0),
+            0.5,
+            array![
+                [0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 1, 1, 1, 0, 0],
+                [0, 0, 1, 1, 1, 1, 1, 0],
+                [0, 0, 0, 1, 1, 1, 0, 0],
+            ],
+        );
+    }
+
+    fn test_draw_filled_circle(center: (f32, f32), radius: f32, expected: Array2<u32>) {
+        let mut buffer = Buffer::new(Array2::from_elem(expected.dim(), 0u32));
+        buffer.draw_filled_circle(
+            SNPoint::new(Point2::new(center.0, center.1)),
+            UNFloat::new(radius),
+            1,
+        );
+        assert!(
+            buffer.array == expected,
+            "mismatching arrays:\nGot:\n{}\nExpected:\n{}",
+            &buffer.array,
+            &expected
+        );
+    }
+
+    fn test_draw_circle(center: (f32, f32), radius: f32, expected: Array2<u32>) {
+        let mut buffer = Buffer::new(Array2::from_elem(expected.dim(), 0u32));
+        buffer.draw_circle(
+            SNPoint::new(Point2::new(center.0, center.1)),
+            UNFloat::new(radius),
+            1,
+        );
+        assert!(
+            buffer.array == expected,
+            "mismatching arrays:\nGot:\n{}\nExpected:\n{}",
+            &buffer.array,
+            &expected
+        );
+    }
+
+    #[test]
+    fn draw_filled_rect_tests() {
+        test_draw_filled_rect(
+            (-0.5, -0.5),
+            (0.0, 0.0),
+            array![[0, 0, 0, 0], [0, 1, 1, 0], [0, 1, 1, 0], [0, 0, 0, 0],],
+        );
+
+        test_draw_filled_rect(
+            (1.0, 1.0),
+            (0.0, -1.0),
+            array![[0, 0, 1, 1], [0, 0, 1, 1], [0, 0, 1, 1], [0, 0, 1, 1],],
+        );
+
+        test_draw_filled_rect(
+            (-1.0, -1.0),
+            (-1.0, -1.0),
+            array![[1, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],],
+        );
+
+        test_draw_filled_rect(
+            (-1.0, -1.0),
+            (-0.2, -0.4),
+            array![[1, 1, 1, 0, 0], [0, 0, 0, 0, 0], [0, 0, 0, 0, 0]],
+        );
+    }
+
+    fn test_draw_filled_rect(corner_a: (f32, f32), corner_b: (f32, f32), expected: Array2<u32>) {
+        let mut buffer = Buffer::new(Array2::from_elem(expected.dim(), 0u32));
+        buffer.draw_filled_rect(
+            SNPoint::new(Point2::new(corner_a.0, corner_a.1)),
+            SNPoint::new(Point2::new(corner_b.0, corner_b.1)),
+            1,
+        );
+        assert!(
+            buffer.array == expected,
+            "mismatching arrays:\nGot:\n{}\nExpected:\n{}",
+            &buffer.array,
+            &expected
+        );
+    }
+
     fn test_draw_line(from: (f32, f32), to: (f32, f32), expected: Array2<u32>) {
         let mut buffer = Buffer::new(Array2::from_elem(expected.dim(), 0u32));
         buffer.draw_line(
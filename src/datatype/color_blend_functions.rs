@@ -1,9 +1,11 @@
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     datatype::{colors::*, continuous::*, discrete::*},
     mutagen_args::*,
+    util::DeterministicRng,
 };
 
 #[derive(
@@ -14,13 +16,68 @@ pub enum ColorBlendFunctions {
     Dissolve,
     Overlay,
     ScreenDodge,
+    Multiply,
+    Add,
+    Subtract,
+    Difference,
+    Darken,
+    Lighten,
+    HardLight,
+    SoftLight,
+    ColorBurn,
 }
 
 impl ColorBlendFunctions {
+    pub fn values() -> &'static [Self] {
+        &[
+            Self::Dissolve,
+            Self::Overlay,
+            Self::ScreenDodge,
+            Self::Multiply,
+            Self::Add,
+            Self::Subtract,
+            Self::Difference,
+            Self::Darken,
+            Self::Lighten,
+            Self::HardLight,
+            Self::SoftLight,
+            Self::ColorBurn,
+        ]
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        *Self::values().choose(rng).unwrap()
+    }
+
     pub fn blend(self, a: FloatColor, b: FloatColor) -> FloatColor {
+        self.blend_with_rng(a, b, &mut thread_rng())
+    }
+
+    /// Blends with a stable Dissolve pattern: the same pixel and seed always pick the same side
+    pub fn blend_at(
+        self,
+        a: FloatColor,
+        b: FloatColor,
+        x: usize,
+        y: usize,
+        seed: u64,
+    ) -> FloatColor {
+        let coordinate_hash = seed
+            ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+        self.blend_with_rng(a, b, &mut DeterministicRng::seed_from_u64(coordinate_hash))
+    }
+
+    pub fn blend_with_rng<R: Rng + ?Sized>(
+        self,
+        a: FloatColor,
+        b: FloatColor,
+        rng: &mut R,
+    ) -> FloatColor {
         match self {
             Self::Dissolve => {
-                if Boolean::random(&mut rand::thread_rng()).into_inner() {
+                if Boolean::random(rng).into_inner() {
                     a
                 } else {
                     b
@@ -37,41 +94,101 @@ impl ColorBlendFunctions {
 
                 FloatColor {
                     r: UNFloat::new(if ar < 0.5 {
-                        (2.0 * ar * br).max(1.0)
+                        (2.0 * ar * br).min(1.0)
                     } else {
                         1.0 - (2.0 * ((1.0 - ar) * (1.0 - br)))
                     }),
                     g: UNFloat::new(if ag < 0.5 {
-                        (2.0 * ag * bg).max(1.0)
+                        (2.0 * ag * bg).min(1.0)
                     } else {
                         1.0 - (2.0 * ((1.0 - ag) * (1.0 - bg)))
                     }),
                     b: UNFloat::new(if ab < 0.5 {
-                        (2.0 * ab * bb).max(1.0)
+                        (2.0 * ab * bb).min(1.0)
                     } else {
                         1.0 - (2.0 * ((1.0 - ab) * (1.0 - bb)))
                     }),
-                    a: UNFloat::new((a.a.into_inner() + b.a.into_inner()) * 0.5),
+                    a: mean_alpha(a, b),
                 }
             }
-            Self::ScreenDodge => {
-                let ar = a.r.into_inner();
-                let ag = a.g.into_inner();
-                let ab = a.b.into_inner();
-
-                let br = b.r.into_inner();
-                let bg = b.g.into_inner();
-                let bb = b.b.into_inner();
+            Self::ScreenDodge => FloatColor {
+                a: mean_alpha(a, b),
+                ..a.screen(b)
+            },
+            Self::Multiply => FloatColor {
+                a: mean_alpha(a, b),
+                ..a.multiply(b)
+            },
+            Self::Add => FloatColor {
+                a: mean_alpha(a, b),
+                ..a.add_clamped(b)
+            },
+            Self::Subtract => blend_channels(a, b, |a, b| a - b),
+            Self::Difference => FloatColor {
+                a: mean_alpha(a, b),
+                ..a.difference(b)
+            },
+            Self::Darken => blend_channels(a, b, f32::min),
+            Self::Lighten => blend_channels(a, b, f32::max),
+            Self::HardLight => blend_channels(a, b, |a, b| {
+                if b < 0.5 {
+                    2.0 * a * b
+                } else {
+                    1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+                }
+            }),
+            // The W3C compositing spec's soft light, which unlike the simpler formulas is
+            // continuous in both inputs
+            Self::SoftLight => blend_channels(a, b, |a, b| {
+                if b <= 0.5 {
+                    a - (1.0 - 2.0 * b) * a * (1.0 - a)
+                } else {
+                    let d = if a <= 0.25 {
+                        ((16.0 * a - 12.0) * a + 4.0) * a
+                    } else {
+                        a.sqrt()
+                    };
 
-                FloatColor {
-                    r: UNFloat::new(1.0 - ((1.0 - ar) * (1.0 - br))),
-                    g: UNFloat::new(1.0 - ((1.0 - ag) * (1.0 - bg))),
-                    b: UNFloat::new(1.0 - ((1.0 - ab) * (1.0 - bb))),
-                    a: UNFloat::new((a.a.into_inner() + b.a.into_inner()) * 0.5),
+                    a + (2.0 * b - 1.0) * (d - a)
                 }
-            }
+            }),
+            Self::ColorBurn => blend_channels(a, b, |a, b| {
+                if a >= 1.0 {
+                    1.0
+                } else if b <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - a) / b).min(1.0)
+                }
+            }),
         }
     }
+
+    /// Blends src onto dst in premultiplied space and composites with src-over alpha,
+    /// returning a premultiplied colour
+    pub fn blend_over(self, src: FloatColor, dst: FloatColor) -> FloatColor {
+        // The blend only shows where dst is opaque, elsewhere src composites unchanged
+        let mixed = src.lerp(self.blend(dst, src), dst.a);
+
+        FloatColor { a: src.a, ..mixed }.over(dst).premultiplied()
+    }
+}
+
+/// All the modes other than Dissolve average the alpha of the two layers
+fn mean_alpha(a: FloatColor, b: FloatColor) -> UNFloat {
+    UNFloat::new((a.a.into_inner() + b.a.into_inner()) * 0.5)
+}
+
+/// Applies f to each colour channel with a as the base layer, clamping the result
+fn blend_channels(a: FloatColor, b: FloatColor, f: impl Fn(f32, f32) -> f32) -> FloatColor {
+    let channel = |a: UNFloat, b: UNFloat| UNFloat::new_clamped(f(a.into_inner(), b.into_inner()));
+
+    FloatColor {
+        r: channel(a.r, b.r),
+        g: channel(a.g, b.g),
+        b: channel(a.b, b.b),
+        a: mean_alpha(a, b),
+    }
 }
 
 impl<'a> Updatable<'a> for ColorBlendFunctions {
@@ -79,3 +196,325 @@ impl<'a> Updatable<'a> for ColorBlendFunctions {
 
     fn update(&mut self, _arg: Self::UpdateArg) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_lists_every_variant() {
+        // Exhaustive so that adding a variant fails to compile until it is listed here
+        let index = |f: ColorBlendFunctions| match f {
+            ColorBlendFunctions::Dissolve => 0,
+            ColorBlendFunctions::Overlay => 1,
+            ColorBlendFunctions::ScreenDodge => 2,
+            ColorBlendFunctions::Multiply => 3,
+            ColorBlendFunctions::Add => 4,
+            ColorBlendFunctions::Subtract => 5,
+            ColorBlendFunctions::Difference => 6,
+            ColorBlendFunctions::Darken => 7,
+            ColorBlendFunctions::Lighten => 8,
+            ColorBlendFunctions::HardLight => 9,
+            ColorBlendFunctions::SoftLight => 10,
+            ColorBlendFunctions::ColorBurn => 11,
+        };
+        let variant_count = 12;
+
+        let values = ColorBlendFunctions::values();
+        assert_eq!(values.len(), variant_count);
+
+        for (i, &f) in values.iter().enumerate() {
+            assert_eq!(index(f), i);
+        }
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let mut seen = [false; 12];
+
+        for _ in 0..1000 {
+            seen[index(ColorBlendFunctions::random(&mut rng))] = true;
+        }
+
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_seeded_dissolve() {
+        let blend = |rng: &mut DeterministicRng| {
+            (0..64)
+                .map(|_| {
+                    ColorBlendFunctions::Dissolve.blend_with_rng(
+                        FloatColor::WHITE,
+                        FloatColor::BLACK,
+                        rng,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = blend(&mut DeterministicRng::seed_from_u64(7));
+
+        assert_eq!(first, blend(&mut DeterministicRng::seed_from_u64(7)));
+        assert!(first.contains(&FloatColor::WHITE) && first.contains(&FloatColor::BLACK));
+
+        let frame = |seed| {
+            (0..64)
+                .map(|i| {
+                    ColorBlendFunctions::Dissolve.blend_at(
+                        FloatColor::WHITE,
+                        FloatColor::BLACK,
+                        i % 8,
+                        i / 8,
+                        seed,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(frame(3), frame(3));
+        assert_ne!(frame(3), frame(4));
+    }
+
+    #[test]
+    fn test_overlay() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let grey = |v: f32| FloatColor {
+            r: UNFloat::new(v),
+            g: UNFloat::new(v),
+            b: UNFloat::new(v),
+            a: UNFloat::ONE,
+        };
+
+        for _ in 0..100 {
+            let color = FloatColor::random(&mut rng);
+
+            // A black base stays black whatever is overlaid on it
+            let over_black = ColorBlendFunctions::Overlay.blend(FloatColor::BLACK, color);
+            assert!(over_black.r.into_inner() < 1e-6);
+            assert!(over_black.g.into_inner() < 1e-6);
+            assert!(over_black.b.into_inner() < 1e-6);
+
+            // Mid grey is the neutral overlay
+            let neutral = ColorBlendFunctions::Overlay.blend(color, grey(0.5));
+            assert!(neutral.r.approx_eq(color.r, 1e-6));
+            assert!(neutral.g.approx_eq(color.g, 1e-6));
+            assert!(neutral.b.approx_eq(color.b, 1e-6));
+        }
+
+        // Overlaying a grey on itself is the contrast S-curve
+        for (v, expected) in [
+            (0.0, 0.0),
+            (0.25, 0.125),
+            (0.5, 0.5),
+            (0.75, 0.875),
+            (1.0, 1.0),
+        ] {
+            let curved = ColorBlendFunctions::Overlay.blend(grey(v), grey(v));
+            assert!(curved.r.approx_eq(UNFloat::new(expected), 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_channel_modes() {
+        let rgb = |r: f32, g: f32, b: f32| FloatColor {
+            r: UNFloat::new(r),
+            g: UNFloat::new(g),
+            b: UNFloat::new(b),
+            a: UNFloat::ONE,
+        };
+
+        let pairs = [
+            (rgb(0.25, 0.5, 0.75), rgb(0.5, 0.75, 0.25)),
+            (rgb(0.0, 1.0, 0.5), rgb(1.0, 0.0, 0.5)),
+        ];
+
+        let expected = [
+            (
+                ColorBlendFunctions::Multiply,
+                [rgb(0.125, 0.375, 0.1875), rgb(0.0, 0.0, 0.25)],
+            ),
+            (
+                ColorBlendFunctions::Add,
+                [rgb(0.75, 1.0, 1.0), rgb(1.0, 1.0, 1.0)],
+            ),
+            (
+                ColorBlendFunctions::Subtract,
+                [rgb(0.0, 0.0, 0.5), rgb(0.0, 1.0, 0.0)],
+            ),
+            (
+                ColorBlendFunctions::Difference,
+                [rgb(0.25, 0.25, 0.5), rgb(1.0, 1.0, 0.0)],
+            ),
+            (
+                ColorBlendFunctions::Darken,
+                [rgb(0.25, 0.5, 0.25), rgb(0.0, 0.0, 0.5)],
+            ),
+            (
+                ColorBlendFunctions::Lighten,
+                [rgb(0.5, 0.75, 0.75), rgb(1.0, 1.0, 0.5)],
+            ),
+            (
+                ColorBlendFunctions::HardLight,
+                [rgb(0.25, 0.75, 0.375), rgb(1.0, 0.0, 0.5)],
+            ),
+            (
+                ColorBlendFunctions::SoftLight,
+                [
+                    rgb(0.25, 0.5 + 0.5 * (0.5f32.sqrt() - 0.5), 0.65625),
+                    rgb(0.0, 1.0, 0.5),
+                ],
+            ),
+            (
+                ColorBlendFunctions::ColorBurn,
+                [rgb(0.0, 1.0 / 3.0, 0.0), rgb(0.0, 1.0, 0.0)],
+            ),
+        ];
+
+        for (blend, expected) in expected {
+            for (&(a, b), expected) in pairs.iter().zip(expected) {
+                let blended = blend.blend(a, b);
+
+                for (channel, expected) in [
+                    (blended.r, expected.r),
+                    (blended.g, expected.g),
+                    (blended.b, expected.b),
+                    (blended.a, expected.a),
+                ] {
+                    assert!(
+                        channel.approx_eq(expected, 1e-6),
+                        "{:?}: got {:?}, expected {:?}",
+                        blend,
+                        blended,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_modes_match_reference_formulas() {
+        fn overlay(a: f32, b: f32) -> f32 {
+            if a < 0.5 {
+                2.0 * a * b
+            } else {
+                1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+            }
+        }
+
+        fn soft_light(a: f32, b: f32) -> f32 {
+            if b <= 0.5 {
+                a - (1.0 - 2.0 * b) * a * (1.0 - a)
+            } else if a <= 0.25 {
+                a + (2.0 * b - 1.0) * (((16.0 * a - 12.0) * a + 4.0) * a - a)
+            } else {
+                a + (2.0 * b - 1.0) * (a.sqrt() - a)
+            }
+        }
+
+        let reference = |blend, a: f32, b: f32| match blend {
+            ColorBlendFunctions::Overlay => overlay(a, b),
+            ColorBlendFunctions::ScreenDodge => 1.0 - (1.0 - a) * (1.0 - b),
+            ColorBlendFunctions::Multiply => a * b,
+            ColorBlendFunctions::HardLight => overlay(b, a),
+            ColorBlendFunctions::SoftLight => soft_light(a, b),
+            _ => unreachable!(),
+        };
+
+        // Either side of and exactly on the 0.5 boundary where the piecewise modes switch
+        let levels = [0.0, 0.2, 0.49, 0.5, 0.51, 0.8, 1.0];
+
+        for blend in [
+            ColorBlendFunctions::Overlay,
+            ColorBlendFunctions::ScreenDodge,
+            ColorBlendFunctions::Multiply,
+            ColorBlendFunctions::HardLight,
+            ColorBlendFunctions::SoftLight,
+        ] {
+            for &a in &levels {
+                for &b in &levels {
+                    let grey = |v: f32| FloatColor {
+                        r: UNFloat::new(v),
+                        g: UNFloat::new(v),
+                        b: UNFloat::new(v),
+                        a: UNFloat::ONE,
+                    };
+
+                    let blended = blend.blend(grey(a), grey(b));
+
+                    assert!(
+                        blended
+                            .r
+                            .approx_eq(UNFloat::new(reference(blend, a, b)), 1e-6),
+                        "{:?}({}, {}) gave {:?}",
+                        blend,
+                        a,
+                        b,
+                        blended.r
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_blend_over_opaque_source() {
+        let src = FloatColor {
+            r: UNFloat::new(0.9),
+            g: UNFloat::new(0.2),
+            b: UNFloat::new(0.4),
+            a: UNFloat::ONE,
+        };
+        let dst = FloatColor {
+            r: UNFloat::new(0.1),
+            g: UNFloat::new(0.8),
+            b: UNFloat::new(0.3),
+            a: UNFloat::ZERO,
+        };
+
+        for blend in [
+            ColorBlendFunctions::Overlay,
+            ColorBlendFunctions::ScreenDodge,
+        ] {
+            assert_eq!(blend.blend_over(src, dst), src);
+        }
+
+        assert_eq!(src.over(dst), src);
+        assert_eq!(dst.over(src), src);
+    }
+
+    #[test]
+    fn test_blend_over_matches_premultiplied_formula() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        // Dissolve draws fresh noise on every call, so it can't be compared against a second blend
+        let blends = ColorBlendFunctions::values()
+            .iter()
+            .filter(|b| !matches!(b, ColorBlendFunctions::Dissolve))
+            .cycle()
+            .take(1000);
+
+        for &blend in blends {
+            let src = FloatColor::random(&mut rng);
+            let dst = FloatColor::random(&mut rng);
+            let blended = blend.blend(dst, src);
+
+            let (sa, da) = (src.a.into_inner(), dst.a.into_inner());
+            let expected = |s: UNFloat, d: UNFloat, b: UNFloat| {
+                let s_mixed = (1.0 - da) * s.into_inner() + da * b.into_inner();
+
+                sa * s_mixed + (1.0 - sa) * da * d.into_inner()
+            };
+
+            let result = blend.blend_over(src, dst);
+
+            for (actual, expected) in [
+                (result.r, expected(src.r, dst.r, blended.r)),
+                (result.g, expected(src.g, dst.g, blended.g)),
+                (result.b, expected(src.b, dst.b, blended.b)),
+                (result.a, sa + da * (1.0 - sa)),
+            ] {
+                assert!((actual.into_inner() - expected).abs() < 1e-4);
+            }
+        }
+    }
+}
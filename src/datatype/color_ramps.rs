@@ -0,0 +1,397 @@
+use failure::{ensure, Fallible};
+use float_ord::FloatOrd;
+use mutagen::{Generatable, Mutatable, Reborrow, Updatable, UpdatableRecursively};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Generatable, Mutatable, UpdatableRecursively,
+)]
+#[mutagen(gen_arg = type ProtoGenArg<'a>, mut_arg = type ProtoMutArg<'a>)]
+pub enum ColorRampInterpolation {
+    Linear,
+    Smoothstep,
+    Constant,
+    Oklab,
+}
+
+impl ColorRampInterpolation {
+    pub fn interpolate(self, a: FloatColor, b: FloatColor, scalar: UNFloat) -> FloatColor {
+        match self {
+            Self::Linear => a.lerp(b, scalar),
+            Self::Smoothstep => {
+                let s = scalar.into_inner();
+                a.lerp(b, UNFloat::new_clamped(s * s * (3.0 - 2.0 * s)))
+            }
+            Self::Constant => a,
+            Self::Oklab => a.lerp_oklab(b, scalar),
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for ColorRampInterpolation {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: Self::UpdateArg) {}
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ColorStop {
+    pub position: UNFloat,
+    pub color: FloatColor,
+}
+
+/// A gradient between colour stops, also usable as a banded palette with Constant interpolation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "ColorRampFields")]
+pub struct ColorRamp {
+    stops: Vec<ColorStop>,
+    pub interpolation: ColorRampInterpolation,
+}
+
+#[derive(Deserialize)]
+struct ColorRampFields {
+    stops: Vec<ColorStop>,
+    interpolation: ColorRampInterpolation,
+}
+
+impl TryFrom<ColorRampFields> for ColorRamp {
+    type Error = failure::Error;
+
+    fn try_from(fields: ColorRampFields) -> Fallible<Self> {
+        Self::try_new(fields.stops, fields.interpolation)
+    }
+}
+
+impl ColorRamp {
+    #[track_caller]
+    pub fn new(stops: Vec<ColorStop>, interpolation: ColorRampInterpolation) -> Self {
+        Self::try_new(stops, interpolation).unwrap()
+    }
+
+    /// Sorts the stops by position, failing if there are none or any lies outside [0, 1]
+    pub fn try_new(
+        mut stops: Vec<ColorStop>,
+        interpolation: ColorRampInterpolation,
+    ) -> Fallible<Self> {
+        stops.sort_by_key(|s| FloatOrd(s.position.into_inner()));
+
+        let ramp = Self {
+            stops,
+            interpolation,
+        };
+
+        ramp.validate()?;
+        Ok(ramp)
+    }
+
+    /// Checks the invariants every constructor upholds
+    pub fn validate(&self) -> Fallible<()> {
+        ensure!(
+            !self.stops.is_empty(),
+            "ColorRamp must have at least one stop"
+        );
+
+        for stop in &self.stops {
+            let position = stop.position.into_inner();
+
+            ensure!(
+                (0.0..=1.0).contains(&position),
+                "ColorStop position must be between 0 and 1, got {}",
+                position
+            );
+        }
+
+        ensure!(
+            self.stops
+                .windows(2)
+                .all(|w| w[0].position <= w[1].position),
+            "ColorRamp stops must be sorted by position"
+        );
+
+        Ok(())
+    }
+
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    pub fn sample(&self, t: UNFloat) -> FloatColor {
+        let next_idx = self.stops.iter().position(|s| s.position > t);
+
+        match next_idx {
+            None => self.stops.last().unwrap().color,
+            Some(0) => self.stops[0].color,
+            Some(i) => {
+                let prev = self.stops[i - 1];
+                let next = self.stops[i];
+
+                let scalar = UNFloat::new_clamped(
+                    (t.into_inner() - prev.position.into_inner())
+                        / (next.position.into_inner() - prev.position.into_inner()),
+                );
+
+                self.interpolation
+                    .interpolate(prev.color, next.color, scalar)
+            }
+        }
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, mut arg: ProtoGenArg) -> Self {
+        let stop_count = rng.gen_range(2..=8);
+
+        Self::new(
+            (0..stop_count)
+                .map(|_| ColorStop {
+                    position: UNFloat::random(rng),
+                    color: FloatColor::random(rng),
+                })
+                .collect(),
+            ColorRampInterpolation::generate_rng(rng, arg.reborrow()),
+        )
+    }
+}
+
+impl<'a> Generatable<'a> for ColorRamp {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng, arg)
+    }
+}
+
+impl<'a> Mutatable<'a> for ColorRamp {
+    type MutArg = ProtoMutArg<'a>;
+
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen::<bool>() {
+            self.interpolation.mutate_rng(rng, arg);
+        } else {
+            *self = Self::random(rng, arg.into());
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for ColorRamp {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for ColorRamp {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+/// A transfer curve from UNFloat to UNFloat, stored as a greyscale ColorRamp
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "ColorRamp1DFields")]
+pub struct ColorRamp1D {
+    ramp: ColorRamp,
+}
+
+#[derive(Deserialize)]
+struct ColorRamp1DFields {
+    ramp: ColorRamp,
+}
+
+impl TryFrom<ColorRamp1DFields> for ColorRamp1D {
+    type Error = failure::Error;
+
+    /// Rebuilds the curve from the red channel, so the stored ramp is always greyscale
+    fn try_from(fields: ColorRamp1DFields) -> Fallible<Self> {
+        Self::try_new(
+            fields
+                .ramp
+                .stops()
+                .iter()
+                .map(|stop| (stop.position, stop.color.r))
+                .collect(),
+            fields.ramp.interpolation,
+        )
+    }
+}
+
+impl ColorRamp1D {
+    /// Builds a curve through (input, output) stops
+    #[track_caller]
+    pub fn new(stops: Vec<(UNFloat, UNFloat)>, interpolation: ColorRampInterpolation) -> Self {
+        Self::try_new(stops, interpolation).unwrap()
+    }
+
+    /// Like new, but fails instead of panicking on stops ColorRamp::try_new rejects
+    pub fn try_new(
+        stops: Vec<(UNFloat, UNFloat)>,
+        interpolation: ColorRampInterpolation,
+    ) -> Fallible<Self> {
+        Ok(Self {
+            ramp: ColorRamp::try_new(
+                stops
+                    .into_iter()
+                    .map(|(position, value)| ColorStop {
+                        position,
+                        color: FloatColor {
+                            r: value,
+                            g: value,
+                            b: value,
+                            a: UNFloat::ONE,
+                        },
+                    })
+                    .collect(),
+                interpolation,
+            )?,
+        })
+    }
+
+    pub fn identity() -> Self {
+        Self::new(
+            vec![(UNFloat::ZERO, UNFloat::ZERO), (UNFloat::ONE, UNFloat::ONE)],
+            ColorRampInterpolation::Linear,
+        )
+    }
+
+    pub fn sample(&self, t: UNFloat) -> UNFloat {
+        self.ramp.sample(t).r
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, mut arg: ProtoGenArg) -> Self {
+        let stop_count = rng.gen_range(2..=8);
+
+        Self::new(
+            (0..stop_count)
+                .map(|_| (UNFloat::random(rng), UNFloat::random(rng)))
+                .collect(),
+            ColorRampInterpolation::generate_rng(rng, arg.reborrow()),
+        )
+    }
+}
+
+impl<'a> Generatable<'a> for ColorRamp1D {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng, arg)
+    }
+}
+
+impl<'a> Mutatable<'a> for ColorRamp1D {
+    type MutArg = ProtoMutArg<'a>;
+
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen::<bool>() {
+            self.ramp.interpolation.mutate_rng(rng, arg);
+        } else {
+            *self = Self::random(rng, arg.into());
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for ColorRamp1D {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for ColorRamp1D {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_abs_diff_eq;
+
+    fn black_to_white(interpolation: ColorRampInterpolation) -> ColorRamp {
+        ColorRamp::new(
+            vec![
+                ColorStop {
+                    position: UNFloat::ZERO,
+                    color: FloatColor::BLACK,
+                },
+                ColorStop {
+                    position: UNFloat::ONE,
+                    color: FloatColor::WHITE,
+                },
+            ],
+            interpolation,
+        )
+    }
+
+    #[test]
+    fn test_linear() {
+        let ramp = black_to_white(ColorRampInterpolation::Linear);
+
+        assert_abs_diff_eq!(ramp.sample(UNFloat::new(0.5)).r.into_inner(), 0.5);
+        assert_abs_diff_eq!(ramp.sample(UNFloat::new(0.25)).r.into_inner(), 0.25);
+    }
+
+    #[test]
+    fn test_smoothstep() {
+        let ramp = black_to_white(ColorRampInterpolation::Smoothstep);
+
+        assert_abs_diff_eq!(ramp.sample(UNFloat::new(0.5)).r.into_inner(), 0.5);
+        assert_abs_diff_eq!(ramp.sample(UNFloat::new(0.25)).r.into_inner(), 0.15625);
+    }
+
+    #[test]
+    fn test_constant() {
+        let ramp = black_to_white(ColorRampInterpolation::Constant);
+
+        assert_eq!(ramp.sample(UNFloat::new(0.5)), FloatColor::BLACK);
+        assert_eq!(ramp.sample(UNFloat::ONE), FloatColor::WHITE);
+    }
+
+    #[test]
+    fn test_deserialize_validates_stops() {
+        let json = |stops: &[ColorStop]| {
+            format!(
+                r#"{{"stops":{},"interpolation":"Linear"}}"#,
+                serde_json::to_string(stops).unwrap()
+            )
+        };
+
+        let mut stops = black_to_white(ColorRampInterpolation::Linear).stops;
+        stops.reverse();
+
+        let ramp: ColorRamp = serde_json::from_str(&json(&stops)).unwrap();
+        assert_eq!(ramp.stops()[0].color, FloatColor::BLACK);
+        assert_eq!(ramp.stops()[1].color, FloatColor::WHITE);
+
+        assert!(serde_json::from_str::<ColorRamp>(&json(&[])).is_err());
+
+        stops[0].position = UNFloat::new_unchecked(1.5);
+        assert!(serde_json::from_str::<ColorRamp>(&json(&stops)).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_1d_validates_stops() {
+        let json = |stops: &[ColorStop]| {
+            format!(
+                r#"{{"ramp":{{"stops":{},"interpolation":"Linear"}}}}"#,
+                serde_json::to_string(stops).unwrap()
+            )
+        };
+
+        let mut stops = black_to_white(ColorRampInterpolation::Linear).stops;
+        stops.reverse();
+
+        let curve: ColorRamp1D = serde_json::from_str(&json(&stops)).unwrap();
+        assert_abs_diff_eq!(curve.sample(UNFloat::ZERO).into_inner(), 0.0);
+        assert_abs_diff_eq!(curve.sample(UNFloat::new(0.25)).into_inner(), 0.25);
+
+        assert!(serde_json::from_str::<ColorRamp1D>(&json(&[])).is_err());
+    }
+
+    #[test]
+    fn test_oklab() {
+        let ramp = black_to_white(ColorRampInterpolation::Oklab);
+        let mid = ramp.sample(UNFloat::new(0.5));
+
+        // Oklab lightness 0.5 is linear luminance 0.125, about 0.39 once sRGB encoded
+        assert_abs_diff_eq!(mid.r.into_inner(), 0.3883, epsilon = 0.001);
+        assert_abs_diff_eq!(mid.r.into_inner(), mid.g.into_inner(), epsilon = 0.001);
+        assert_abs_diff_eq!(mid.g.into_inner(), mid.b.into_inner(), epsilon = 0.001);
+    }
+}
@@ -1,9 +1,8 @@
 use std::f32::consts::PI;
 
-use approx::abs_diff_eq;
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
 use nalgebra::Complex;
-use palette::{encoding::srgb::Srgb, rgb::Rgb, Hsv, Lab, Limited, RgbHue};
+use palette::{encoding::srgb::Srgb, rgb::Rgb, Hsl, Hsv, Lab, Limited, RgbHue};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -31,10 +30,10 @@ impl<'a> UpdatableRecursively<'a> for NibbleColor {
 impl From<FloatColor> for NibbleColor {
     fn from(other: FloatColor) -> Self {
         Self {
-            r: Nibble::new((other.r.into_inner() * 16.0) as u8),
-            g: Nibble::new((other.g.into_inner() * 16.0) as u8),
-            b: Nibble::new((other.b.into_inner() * 16.0) as u8),
-            a: Nibble::new((other.a.into_inner() * 16.0) as u8),
+            r: Nibble::new(quantize_channel(other.r, 15)),
+            g: Nibble::new(quantize_channel(other.g, 15)),
+            b: Nibble::new(quantize_channel(other.b, 15)),
+            a: Nibble::new(quantize_channel(other.a, 15)),
         }
     }
 }
@@ -61,6 +60,34 @@ impl ByteColor {
             a: self.a,
         }
     }
+
+    fn map_channels(self, other: Self, f: impl Fn(u8, u8) -> u8) -> Self {
+        let channel = |a: Byte, b: Byte| Byte::new(f(a.into_inner(), b.into_inner()));
+
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: self.a,
+        }
+    }
+
+    /// Channel-wise sum capped at 255. Alpha is kept from self.
+    pub fn saturating_add(self, other: Self) -> Self {
+        self.map_channels(other, u8::saturating_add)
+    }
+
+    /// Channel-wise sum modulo 256. Alpha is kept from self.
+    pub fn wrapping_add(self, other: Self) -> Self {
+        self.map_channels(other, u8::wrapping_add)
+    }
+
+    /// Channel-wise product with 255 as one, rounded. Alpha is kept from self.
+    pub fn multiply(self, other: Self) -> Self {
+        self.map_channels(other, |a, b| {
+            ((u16::from(a) * u16::from(b) + 127) / 255) as u8
+        })
+    }
 }
 
 impl<'a> Updatable<'a> for ByteColor {
@@ -87,14 +114,33 @@ impl From<image::Rgba<u8>> for ByteColor {
 impl From<FloatColor> for ByteColor {
     fn from(other: FloatColor) -> Self {
         Self {
-            r: Byte::new((other.r.into_inner() * 255.0) as u8),
-            g: Byte::new((other.g.into_inner() * 255.0) as u8),
-            b: Byte::new((other.b.into_inner() * 255.0) as u8),
-            a: Byte::new((other.a.into_inner() * 255.0) as u8),
+            r: Byte::new(quantize_channel(other.r, 255)),
+            g: Byte::new(quantize_channel(other.g, 255)),
+            b: Byte::new(quantize_channel(other.b, 255)),
+            a: Byte::new(quantize_channel(other.a, 255)),
         }
     }
 }
 
+/// Rotates a palette in place so each colour moves shift entries later, wrapping around the end
+pub fn cycle_palette(colors: &mut [ByteColor], shift: usize) {
+    if !colors.is_empty() {
+        let len = colors.len();
+        colors.rotate_right(shift % len);
+    }
+}
+
+/// Rounds a channel onto 0..=max, treating non-finite values as 0 and clamping anything out of range
+fn quantize_channel(channel: UNFloat, max: u8) -> u8 {
+    let value = channel.into_inner();
+
+    if value.is_finite() {
+        (value.clamp(0.0, 1.0) * f32::from(max)).round() as u8
+    } else {
+        0
+    }
+}
+
 /// Expects all inputs and outputs to be between 0.0 and 1.0
 pub fn rgb_tuple_to_hsv_tuple(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
     let (h, s, v) = Hsv::<Srgb, _>::from(Rgb::<Srgb, _>::new(r, g, b)).into_components();
@@ -111,6 +157,103 @@ pub fn hsv_tuple_to_rgb_tuple(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
     .into_components()
 }
 
+/// Conversion to and from FloatColor, so every colour type can be used interchangeably in generic code
+pub trait ColorConvert: Sized {
+    fn to_float_color(&self) -> FloatColor;
+    fn from_float_color(color: FloatColor) -> Self;
+}
+
+impl ColorConvert for NibbleColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for ByteColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for BitColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for FloatColor {
+    fn to_float_color(&self) -> FloatColor {
+        *self
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        color
+    }
+}
+
+impl ColorConvert for HSVColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for HSLColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for CMYKColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+impl ColorConvert for LABColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(*self)
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(color)
+    }
+}
+
+/// Goes through LABColor, so chroma beyond the LABColor range is clamped
+impl ColorConvert for LChColor {
+    fn to_float_color(&self) -> FloatColor {
+        FloatColor::from(LABColor::from(*self))
+    }
+
+    fn from_float_color(color: FloatColor) -> Self {
+        Self::from(LABColor::from(color))
+    }
+}
+
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum BitColor {
     Black,
@@ -378,56 +521,141 @@ impl FloatColor {
         (self.r.into_inner() + self.b.into_inner() + self.g.into_inner()) / 3.0
     }
 
-    //Translated to rust from an answer here here: https://stackoverflow.com/questions/23090019/fastest-formula-to-get-hue-from-rgb
+    /// Relative luminance using the Rec. 709 coefficients
+    pub fn get_luminance(&self) -> UNFloat {
+        UNFloat::new_clamped(
+            0.2126 * self.r.into_inner()
+                + 0.7152 * self.g.into_inner()
+                + 0.0722 * self.b.into_inner(),
+        )
+    }
+
+    /// Hue, saturation and value computed together in a single conversion
+    pub fn to_hsv_components(&self) -> (UNFloat, UNFloat, UNFloat) {
+        let (h, s, v) = rgb_tuple_to_hsv_tuple(
+            self.r.into_inner(),
+            self.g.into_inner(),
+            self.b.into_inner(),
+        );
+
+        (
+            UNFloat::new_clamped(h),
+            UNFloat::new_clamped(s),
+            UNFloat::new_clamped(v),
+        )
+    }
+
     pub fn get_hue_unfloat(&self) -> UNFloat {
-        let r = self.r.into_inner();
-        let g = self.g.into_inner();
-        let b = self.b.into_inner();
+        self.to_hsv_components().0
+    }
 
-        let min = r.min(g.min(b));
-        let max = r.min(g.min(b));
+    pub fn get_saturation_unfloat(&self) -> UNFloat {
+        self.to_hsv_components().1
+    }
 
-        if abs_diff_eq!(min, max) {
-            UNFloat::new(0.0)
-        } else {
-            let mut hue = if abs_diff_eq!(max, r) {
-                (g - b) / (max - min)
-            } else if abs_diff_eq!(max, g) {
-                2.0 + (b - r) / (max - min)
-            } else {
-                4.0 + (r - g) / (max - min)
-            };
+    pub fn get_value_unfloat(&self) -> UNFloat {
+        self.to_hsv_components().2
+    }
+
+    /// Rotates the hue through HSV, preserving saturation, value and alpha
+    pub fn rotate_hue(self, by: Angle) -> Self {
+        Self::from(HSVColor::from(self).offset_hue(by))
+    }
 
-            hue *= 60.0;
+    /// Scales HSV saturation by 1 + amount, so -1 is fully grey and positive amounts boost
+    pub fn adjust_saturation(self, amount: SNFloat) -> Self {
+        let hsv = HSVColor::from(self);
 
-            if hue < 0.0 {
-                hue += 360.0;
-            }
+        Self::from(HSVColor {
+            s: UNFloat::new_clamped(hsv.s.into_inner() * (1.0 + amount.into_inner())),
+            ..hsv
+        })
+    }
 
-            UNFloat::new(hue / 360.0)
+    /// Shifts lightness in Lab space, keeping hue and chroma where the gamut allows
+    pub fn adjust_lightness_lab(self, delta: SNFloat) -> Self {
+        let lab = LABColor::from(self);
+        let l = (lab.l.into_inner() + delta.into_inner()).clamp(0.0, 1.0);
+
+        Self::from(lab.with_lightness(SNFloat::new(l)))
+    }
+
+    /// The colour with each channel multiplied by alpha
+    pub fn premultiplied(self) -> Self {
+        Self {
+            r: self.r.multiply(self.a),
+            g: self.g.multiply(self.a),
+            b: self.b.multiply(self.a),
+            a: self.a,
         }
     }
 
-    pub fn get_saturation_unfloat(&self) -> UNFloat {
-        UNFloat::new(
-            rgb_tuple_to_hsv_tuple(
-                self.r.into_inner(),
-                self.g.into_inner(),
-                self.b.into_inner(),
-            )
-            .1,
-        )
+    /// Passes each colour channel through its own transfer curve, leaving alpha untouched
+    pub fn apply_curve(self, r: &ColorRamp1D, g: &ColorRamp1D, b: &ColorRamp1D) -> Self {
+        Self {
+            r: r.sample(self.r),
+            g: g.sample(self.g),
+            b: b.sample(self.b),
+            a: self.a,
+        }
     }
 
-    pub fn get_value_unfloat(&self) -> UNFloat {
-        UNFloat::new(
-            rgb_tuple_to_hsv_tuple(
-                self.r.into_inner(),
-                self.g.into_inner(),
-                self.b.into_inner(),
-            )
-            .2,
-        )
+    fn map_channels(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let channel =
+            |a: UNFloat, b: UNFloat| UNFloat::new_clamped(f(a.into_inner(), b.into_inner()));
+
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: self.a,
+        }
+    }
+
+    /// Channel-wise sum capped at one. Alpha is kept from self, as for the other channel-wise
+    /// operations below.
+    pub fn add_clamped(self, other: Self) -> Self {
+        self.map_channels(other, |a, b| a + b)
+    }
+
+    pub fn multiply(self, other: Self) -> Self {
+        self.map_channels(other, |a, b| a * b)
+    }
+
+    /// The inverse of multiplying the inverses, so it only ever lightens
+    pub fn screen(self, other: Self) -> Self {
+        self.map_channels(other, |a, b| 1.0 - (1.0 - a) * (1.0 - b))
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        self.map_channels(other, |a, b| (a - b).abs())
+    }
+
+    /// Inverts the colour channels, leaving alpha untouched
+    pub fn invert(self) -> Self {
+        self.map_channels(self, |a, _| 1.0 - a)
+    }
+
+    /// Composites self over dst using straight (non-premultiplied) alpha
+    pub fn over(self, dst: Self) -> Self {
+        let sa = self.a.into_inner();
+        let da = dst.a.into_inner() * (1.0 - sa);
+        let a = sa + da;
+
+        if a <= 0.0 {
+            return Self::ALL_ZERO;
+        }
+
+        let channel = |s: UNFloat, d: UNFloat| {
+            UNFloat::new_clamped((s.into_inner() * sa + d.into_inner() * da) / a)
+        };
+
+        Self {
+            r: channel(self.r, dst.r),
+            g: channel(self.g, dst.g),
+            b: channel(self.b, dst.b),
+            a: UNFloat::new_clamped(a),
+        }
     }
 
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
@@ -448,6 +676,26 @@ impl FloatColor {
         }
     }
 
+    /// Interpolates in Oklab space for perceptually even transitions
+    pub fn lerp_oklab(self, other: Self, scalar: UNFloat) -> Self {
+        let from = srgb_to_oklab([self.r, self.g, self.b]);
+        let to = srgb_to_oklab([other.r, other.g, other.b]);
+        let s = scalar.into_inner();
+
+        let [r, g, b] = oklab_to_srgb([
+            lerp(from[0], to[0], s),
+            lerp(from[1], to[1], s),
+            lerp(from[2], to[2], s),
+        ]);
+
+        Self {
+            r,
+            g,
+            b,
+            a: self.a.lerp(other.a, scalar),
+        }
+    }
+
     pub const ALL_ZERO: Self = Self {
         r: UNFloat::ZERO,
         g: UNFloat::ZERO,
@@ -468,13 +716,24 @@ impl FloatColor {
     };
 }
 
+impl From<NibbleColor> for FloatColor {
+    fn from(c: NibbleColor) -> FloatColor {
+        FloatColor {
+            r: c.r.to_unfloat(),
+            g: c.g.to_unfloat(),
+            b: c.b.to_unfloat(),
+            a: c.a.to_unfloat(),
+        }
+    }
+}
+
 impl From<ByteColor> for FloatColor {
     fn from(c: ByteColor) -> FloatColor {
         FloatColor {
-            r: UNFloat::new(c.r.into_inner() as f32 / 255.0),
-            g: UNFloat::new(c.g.into_inner() as f32 / 255.0),
-            b: UNFloat::new(c.b.into_inner() as f32 / 255.0),
-            a: UNFloat::new(c.a.into_inner() as f32 / 255.0),
+            r: c.r.to_unfloat(),
+            g: c.g.to_unfloat(),
+            b: c.b.to_unfloat(),
+            a: c.a.to_unfloat(),
         }
     }
 }
@@ -510,6 +769,24 @@ impl From<HSVColor> for FloatColor {
     }
 }
 
+impl From<HSLColor> for FloatColor {
+    fn from(hsl: HSLColor) -> Self {
+        let rgb = Rgb::<Srgb>::from(Hsl::<Srgb, _>::from_components((
+            RgbHue::from_radians(hsl.h.into_inner()),
+            hsl.s.into_inner(),
+            hsl.l.into_inner(),
+        )))
+        .clamp();
+
+        Self {
+            r: UNFloat::new(rgb.red),
+            g: UNFloat::new(rgb.green),
+            b: UNFloat::new(rgb.blue),
+            a: hsl.a,
+        }
+    }
+}
+
 impl From<CMYKColor> for FloatColor {
     fn from(cmyk: CMYKColor) -> Self {
         Self {
@@ -593,7 +870,7 @@ impl HSVColor {
 
     pub fn offset_hue(self, hue: Angle) -> Self {
         Self {
-            h: self.h.add(hue),
+            h: Angle::new_wrapped(self.h.into_inner() + hue.into_inner()),
             s: self.s,
             v: self.v,
             a: self.a,
@@ -631,7 +908,7 @@ impl From<FloatColor> for HSVColor {
         )));
 
         Self {
-            h: Angle::new(hsv.hue.to_radians()),
+            h: Angle::new_unchecked(hsv.hue.to_radians()),
             s: UNFloat::new(hsv.saturation),
             v: UNFloat::new(hsv.value),
             a: rgb.a,
@@ -664,6 +941,97 @@ impl<'a> UpdatableRecursively<'a> for HSVColor {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct HSLColor {
+    pub h: Angle,
+    pub s: UNFloat,
+    pub l: UNFloat,
+    pub a: UNFloat,
+}
+
+impl HSLColor {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            h: Angle::random(rng),
+            s: UNFloat::random(rng),
+            l: UNFloat::random(rng),
+            a: UNFloat::random(rng),
+        }
+    }
+
+    pub fn lerp(self, other: Self, scalar: UNFloat) -> Self {
+        Self {
+            h: self.h.lerp(other.h, scalar),
+            s: self.s.lerp(other.s, scalar),
+            l: self.l.lerp(other.l, scalar),
+            a: self.a.lerp(other.a, scalar),
+        }
+    }
+
+    pub const ALL_ZERO: Self = Self {
+        h: Angle::ZERO,
+        s: UNFloat::ZERO,
+        l: UNFloat::ZERO,
+        a: UNFloat::ZERO,
+    };
+
+    pub const WHITE: Self = Self {
+        h: Angle::ZERO,
+        s: UNFloat::ZERO,
+        l: UNFloat::ONE,
+        a: UNFloat::ONE,
+    };
+
+    pub const BLACK: Self = Self {
+        h: Angle::ZERO,
+        s: UNFloat::ZERO,
+        l: UNFloat::ZERO,
+        a: UNFloat::ONE,
+    };
+}
+
+impl From<FloatColor> for HSLColor {
+    fn from(rgb: FloatColor) -> Self {
+        let hsl = Hsl::from(Rgb::<Srgb, _>::from_components((
+            rgb.r.into_inner(),
+            rgb.g.into_inner(),
+            rgb.b.into_inner(),
+        )));
+
+        Self {
+            h: Angle::new_unchecked(hsl.hue.to_radians()),
+            s: UNFloat::new_clamped(hsl.saturation),
+            l: UNFloat::new_clamped(hsl.lightness),
+            a: rgb.a,
+        }
+    }
+}
+
+impl<'a> Generatable<'a> for HSLColor {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for HSLColor {
+    type MutArg = ProtoMutArg<'a>;
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
+        *self = Self::random(rng);
+    }
+}
+
+impl<'a> Updatable<'a> for HSLColor {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for HSLColor {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
 pub struct CMYKColor {
     pub c: UNFloat,
@@ -793,6 +1161,44 @@ impl LABColor {
         }
     }
 
+    /// Distance of ab from the neutral axis, i.e. the C of LCh
+    pub fn chroma(&self) -> UNFloat {
+        UNFloat::new_clamped(self.ab.into_inner().norm() as f32)
+    }
+
+    /// Angle of ab around the neutral axis, i.e. the h of LCh
+    pub fn hue(&self) -> Angle {
+        let ab = self.ab.into_inner();
+        Angle::new_unchecked(f64::atan2(ab.im, ab.re) as f32)
+    }
+
+    pub fn with_lightness(self, l: SNFloat) -> Self {
+        Self { l, ..self }
+    }
+
+    pub fn with_chroma(self, c: UNFloat) -> Self {
+        let ab = self.ab.into_inner();
+        let norm = ab.norm();
+
+        if norm > 0.0 {
+            Self {
+                ab: clamped_complex(ab * (c.into_inner() as f64 / norm)),
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+
+    pub fn rotate_hue(self, by: Angle) -> Self {
+        Self {
+            ab: clamped_complex(
+                self.ab.into_inner() * Complex::from_polar(1.0, by.into_inner() as f64),
+            ),
+            ..self
+        }
+    }
+
     pub const ALL_ZERO: Self = Self {
         l: SNFloat::ZERO,
         ab: SNComplex::ZERO,
@@ -829,6 +1235,19 @@ impl From<FloatColor> for LABColor {
     }
 }
 
+impl From<LChColor> for LABColor {
+    fn from(lch: LChColor) -> Self {
+        Self {
+            l: lch.l,
+            ab: clamped_complex(Complex::from_polar(
+                lch.c.into_inner() as f64,
+                lch.h.into_inner() as f64,
+            )),
+            alpha: lch.alpha,
+        }
+    }
+}
+
 impl<'a> Generatable<'a> for LABColor {
     type GenArg = ProtoGenArg<'a>;
 
@@ -853,3 +1272,538 @@ impl<'a> Updatable<'a> for LABColor {
 impl<'a> UpdatableRecursively<'a> for LABColor {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
+
+/// Cylindrical view of LABColor, with ab split into chroma and hue
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct LChColor {
+    pub l: SNFloat,
+    pub c: UNFloat,
+    pub h: Angle,
+    pub alpha: UNFloat,
+}
+
+impl LChColor {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::from(LABColor::random(rng))
+    }
+
+    pub fn lerp(self, other: Self, scalar: UNFloat) -> Self {
+        Self {
+            l: self.l.lerp(other.l, scalar),
+            c: self.c.lerp(other.c, scalar),
+            h: self.h.lerp(other.h, scalar),
+            alpha: self.alpha.lerp(other.alpha, scalar),
+        }
+    }
+}
+
+impl From<LABColor> for LChColor {
+    fn from(lab: LABColor) -> Self {
+        Self {
+            l: lab.l,
+            c: lab.chroma(),
+            h: lab.hue(),
+            alpha: lab.alpha,
+        }
+    }
+}
+
+impl<'a> Generatable<'a> for LChColor {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for LChColor {
+    type MutArg = ProtoMutArg<'a>;
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
+        *self = Self::random(rng);
+    }
+}
+
+impl<'a> Updatable<'a> for LChColor {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for LChColor {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+//Matrices from https://bottosson.github.io/posts/oklab/
+#[allow(clippy::excessive_precision)]
+fn srgb_to_oklab(rgb: [UNFloat; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| srgb_to_linear(c.into_inner()));
+
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+#[allow(clippy::excessive_precision)]
+fn oklab_to_srgb(lab: [f32; 3]) -> [UNFloat; 3] {
+    let [l, a, b] = lab;
+
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+    .map(|c| UNFloat::new_clamped(linear_to_srgb(c.max(0.0))))
+}
+
+fn clamped_complex(value: Complex<f64>) -> SNComplex {
+    SNComplex::from_snfloats(
+        SNFloat::new_clamped(value.re as f32),
+        SNFloat::new_clamped(value.im as f32),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_quantize_non_finite_channels() {
+        let color = FloatColor {
+            r: UNFloat::new_unchecked(f32::NAN),
+            g: UNFloat::new_unchecked(f32::INFINITY),
+            b: UNFloat::new_unchecked(1.5),
+            a: UNFloat::new_unchecked(-0.5),
+        };
+
+        let byte = ByteColor::from(color);
+        assert_eq!(
+            [byte.r, byte.g, byte.b, byte.a].map(Byte::into_inner),
+            [0, 0, 255, 0]
+        );
+
+        let nibble = NibbleColor::from(color);
+        assert_eq!(
+            [nibble.r, nibble.g, nibble.b, nibble.a].map(Nibble::into_inner),
+            [0, 0, 15, 0]
+        );
+    }
+
+    #[test]
+    fn test_quantization_boundaries() {
+        let grey = |v: f32| FloatColor {
+            r: UNFloat::new(v),
+            g: UNFloat::new(v),
+            b: UNFloat::new(v),
+            a: UNFloat::new(v),
+        };
+
+        for (v, nibble, byte) in [(0.0, 0, 0), (0.5, 8, 128), (1.0, 15, 255)] {
+            let n = NibbleColor::from(grey(v));
+            let b = ByteColor::from(grey(v));
+
+            for channel in [n.r, n.g, n.b, n.a] {
+                assert_eq!(channel.into_inner(), nibble);
+            }
+
+            for channel in [b.r, b.g, b.b, b.a] {
+                assert_eq!(channel.into_inner(), byte);
+            }
+        }
+
+        // Every level survives a round trip through FloatColor exactly
+        for level in 0..16 {
+            let n = NibbleColor {
+                r: Nibble::new(level),
+                g: Nibble::new(15 - level),
+                b: Nibble::new(level),
+                a: Nibble::new(15),
+            };
+            assert_eq!(NibbleColor::from(FloatColor::from(n)), n);
+        }
+
+        for level in 0..=255 {
+            let b = ByteColor {
+                r: Byte::new(level),
+                g: Byte::new(255 - level),
+                b: Byte::new(level),
+                a: Byte::new(255),
+            };
+            assert_eq!(ByteColor::from(FloatColor::from(b)), b);
+        }
+    }
+
+    #[test]
+    fn test_channel_arithmetic() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let opaque_black = FloatColor {
+            a: UNFloat::ONE,
+            ..FloatColor::BLACK
+        };
+
+        let close = |x: FloatColor, y: FloatColor| {
+            [(x.r, y.r), (x.g, y.g), (x.b, y.b), (x.a, y.a)]
+                .iter()
+                .all(|(x, y)| x.approx_eq(*y, 1e-6))
+        };
+
+        for _ in 0..100 {
+            let a = FloatColor::random(&mut rng);
+            let b = FloatColor::random(&mut rng);
+
+            assert_eq!(a.multiply(FloatColor::WHITE), a);
+            assert!(close(a.screen(FloatColor::BLACK), a));
+            assert_eq!(a.add_clamped(FloatColor::BLACK), a);
+            assert_eq!(
+                a.difference(a),
+                FloatColor {
+                    a: a.a,
+                    ..opaque_black
+                }
+            );
+            assert!(close(a.invert().invert(), a));
+
+            // The channels are clamped on construction, so check they bound the inputs as expected
+            let sum = a.add_clamped(b);
+            assert!(sum.r.into_inner() >= a.r.into_inner().max(b.r.into_inner()));
+            assert!(a.screen(b).g.into_inner() >= a.g.into_inner().max(b.g.into_inner()) - 1e-6);
+            assert!(a.multiply(b).b.into_inner() <= a.b.into_inner().min(b.b.into_inner()));
+            assert_eq!(a.difference(b).r, b.difference(a).r);
+        }
+
+        let byte = |v: u8| ByteColor {
+            r: Byte::new(v),
+            g: Byte::new(v),
+            b: Byte::new(v),
+            a: Byte::new(200),
+        };
+
+        for v in [0, 1, 127, 128, 254, 255] {
+            assert_eq!(byte(v).multiply(byte(255)), byte(v));
+            assert_eq!(byte(v).multiply(byte(0)), byte(0));
+            assert_eq!(byte(v).saturating_add(byte(0)), byte(v));
+            assert_eq!(byte(v).wrapping_add(byte(0)), byte(v));
+        }
+
+        assert_eq!(byte(200).saturating_add(byte(100)), byte(255));
+        assert_eq!(byte(200).wrapping_add(byte(100)), byte(44));
+        assert_eq!(byte(128).multiply(byte(128)), byte(64));
+    }
+
+    #[test]
+    fn test_adjust_saturation() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let color = FloatColor::random(&mut rng);
+
+            let grey = color.adjust_saturation(SNFloat::NEG_ONE);
+            assert_abs_diff_eq!(grey.r.into_inner(), grey.g.into_inner(), epsilon = 1e-5);
+            assert_abs_diff_eq!(grey.g.into_inner(), grey.b.into_inner(), epsilon = 1e-5);
+            assert_eq!(grey.a, color.a);
+
+            let same = color.adjust_saturation(SNFloat::ZERO);
+            assert_abs_diff_eq!(same.r.into_inner(), color.r.into_inner(), epsilon = 1e-5);
+            assert_abs_diff_eq!(same.g.into_inner(), color.g.into_inner(), epsilon = 1e-5);
+            assert_abs_diff_eq!(same.b.into_inner(), color.b.into_inner(), epsilon = 1e-5);
+
+            let boosted = HSVColor::from(color.adjust_saturation(SNFloat::new(0.5)));
+            assert!(boosted.s.into_inner() >= HSVColor::from(color).s.into_inner() - 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_apply_curve() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let identity = ColorRamp1D::identity();
+        let invert = ColorRamp1D::new(
+            vec![(UNFloat::ZERO, UNFloat::ONE), (UNFloat::ONE, UNFloat::ZERO)],
+            ColorRampInterpolation::Linear,
+        );
+
+        for _ in 0..1000 {
+            let color = FloatColor::random(&mut rng);
+
+            assert_eq!(color.apply_curve(&identity, &identity, &identity), color);
+
+            let graded = color.apply_curve(&invert, &identity, &identity);
+            assert_abs_diff_eq!(
+                graded.r.into_inner(),
+                1.0 - color.r.into_inner(),
+                epsilon = 1e-6
+            );
+            assert_eq!(graded.a, color.a);
+        }
+    }
+
+    #[test]
+    fn test_hue_of_primaries() {
+        for (rgb, hue) in [
+            ((1.0, 0.0, 0.0), 0.0),
+            ((0.0, 1.0, 0.0), 1.0 / 3.0),
+            ((0.0, 0.0, 1.0), 2.0 / 3.0),
+            ((1.0, 1.0, 0.0), 1.0 / 6.0),
+        ] {
+            let color = FloatColor {
+                r: UNFloat::new(rgb.0),
+                g: UNFloat::new(rgb.1),
+                b: UNFloat::new(rgb.2),
+                a: UNFloat::ONE,
+            };
+
+            assert_abs_diff_eq!(color.get_hue_unfloat().into_inner(), hue, epsilon = 0.001);
+            assert_abs_diff_eq!(
+                color.get_hue_unfloat().into_inner(),
+                rgb_tuple_to_hsv_tuple(rgb.0, rgb.1, rgb.2).0,
+                epsilon = 0.001
+            );
+        }
+    }
+
+    #[test]
+    fn test_hue_across_rgb_grid() {
+        // The textbook piecewise hue, to check the palette conversion independently. Palette
+        // takes the hue of the linear light values, so the channels are decoded from sRGB first.
+        let decode = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let reference_hue = |r: f32, g: f32, b: f32| {
+            let (r, g, b) = (decode(r), decode(g), decode(b));
+            let max = r.max(g.max(b));
+            let chroma = max - r.min(g.min(b));
+
+            let sector = if max == r {
+                ((g - b) / chroma).rem_euclid(6.0)
+            } else if max == g {
+                (b - r) / chroma + 2.0
+            } else {
+                (r - g) / chroma + 4.0
+            };
+
+            sector / 6.0
+        };
+
+        let levels: Vec<f32> = (0..=8).map(|i| i as f32 / 8.0).collect();
+
+        for &r in &levels {
+            for &g in &levels {
+                for &b in &levels {
+                    let color = FloatColor {
+                        r: UNFloat::new(r),
+                        g: UNFloat::new(g),
+                        b: UNFloat::new(b),
+                        a: UNFloat::ONE,
+                    };
+                    let hue = color.get_hue_unfloat().into_inner();
+
+                    assert_abs_diff_eq!(hue, rgb_tuple_to_hsv_tuple(r, g, b).0, epsilon = 1e-4);
+
+                    // Hue is undefined for greys
+                    if r.max(g.max(b)) - r.min(g.min(b)) < 1e-6 {
+                        continue;
+                    }
+
+                    let difference = (hue - reference_hue(r, g, b)).abs();
+                    assert!(
+                        difference.min(1.0 - difference) < 1e-4,
+                        "hue of ({}, {}, {}) was {}",
+                        r,
+                        g,
+                        b,
+                        hue
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hsv_getters_match_hsv_color() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let color = FloatColor::random(&mut rng);
+            let hsv = HSVColor::from(color);
+
+            let hue_diff = (color.get_hue_unfloat().into_inner()
+                - hsv.h.into_inner().rem_euclid(2.0 * PI) / (2.0 * PI))
+                .abs();
+
+            assert!(hue_diff.min(1.0 - hue_diff) < 0.0001);
+            assert_abs_diff_eq!(
+                color.get_saturation_unfloat().into_inner(),
+                hsv.s.into_inner(),
+                epsilon = 0.0001
+            );
+            assert_abs_diff_eq!(
+                color.get_value_unfloat().into_inner(),
+                hsv.v.into_inner(),
+                epsilon = 0.0001
+            );
+        }
+    }
+
+    fn assert_round_trip<T: ColorConvert>(tolerance: f32, filter: impl Fn(FloatColor) -> bool) {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let color = FloatColor::random(&mut rng);
+
+            if !filter(color) {
+                continue;
+            }
+
+            let round_trip = T::from_float_color(color).to_float_color();
+
+            for (a, b) in [
+                (color.r, round_trip.r),
+                (color.g, round_trip.g),
+                (color.b, round_trip.b),
+                (color.a, round_trip.a),
+            ] {
+                assert_abs_diff_eq!(a.into_inner(), b.into_inner(), epsilon = tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_convert_round_trips() {
+        assert_round_trip::<FloatColor>(0.0, |_| true);
+        assert_round_trip::<ByteColor>(0.5 / 255.0 + 1e-6, |_| true);
+        assert_round_trip::<NibbleColor>(0.5 / 15.0 + 1e-6, |_| true);
+        assert_round_trip::<HSVColor>(1e-4, |_| true);
+        assert_round_trip::<HSLColor>(1e-4, |c| {
+            let hsl = HSLColor::from(c);
+            hsl.s.into_inner() > 1e-3 && hsl.l.into_inner() > 1e-3 && hsl.l.into_inner() < 0.999
+        });
+        assert_round_trip::<CMYKColor>(1e-4, |_| true);
+        assert_round_trip::<LABColor>(1e-3, |_| true);
+        assert_round_trip::<LChColor>(1e-3, |c| LABColor::from(c).chroma().into_inner() < 1.0);
+
+        for i in 0..8 {
+            let bit = BitColor::from_index(i);
+            assert_eq!(BitColor::from_float_color(bit.to_float_color()), bit);
+        }
+    }
+
+    #[test]
+    fn test_float_color_rotate_hue() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let half_turn = Angle::new_unchecked(PI);
+
+        for _ in 0..100 {
+            let color = FloatColor::random(&mut rng);
+            let rotated = color.rotate_hue(half_turn).rotate_hue(half_turn);
+
+            for (a, b) in [
+                (color.r, rotated.r),
+                (color.g, rotated.g),
+                (color.b, rotated.b),
+                (color.a, rotated.a),
+            ] {
+                assert_abs_diff_eq!(a.into_inner(), b.into_inner(), epsilon = 1e-4);
+            }
+        }
+
+        let red = FloatColor {
+            r: UNFloat::ONE,
+            g: UNFloat::ZERO,
+            b: UNFloat::ZERO,
+            a: UNFloat::ONE,
+        };
+        let green = red.rotate_hue(Angle::new_unchecked(120f32.to_radians()));
+
+        assert_abs_diff_eq!(green.r.into_inner(), 0.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(green.g.into_inner(), 1.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(green.b.into_inner(), 0.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_rotate_hue_full_turn() {
+        let lab = LABColor {
+            l: SNFloat::new(0.5),
+            ab: SNComplex::new(Complex::new(0.3, -0.2)),
+            alpha: UNFloat::ONE,
+        };
+
+        let half_turn = Angle::new_unchecked(PI);
+        let rotated = lab.rotate_hue(half_turn).rotate_hue(half_turn);
+
+        assert_abs_diff_eq!(rotated.ab.re().into_inner(), 0.3, epsilon = 0.0001);
+        assert_abs_diff_eq!(rotated.ab.im().into_inner(), -0.2, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_with_lightness_preserves_hue_and_chroma() {
+        let color = FloatColor {
+            r: UNFloat::new(0.6),
+            g: UNFloat::new(0.45),
+            b: UNFloat::new(0.35),
+            a: UNFloat::ONE,
+        };
+
+        let lab = LABColor::from(color);
+        let adjusted = LABColor::from(FloatColor::from(
+            lab.with_lightness(SNFloat::new(lab.l.into_inner() + 0.1)),
+        ));
+
+        assert_abs_diff_eq!(
+            adjusted.l.into_inner(),
+            lab.l.into_inner() + 0.1,
+            epsilon = 0.01
+        );
+        assert_abs_diff_eq!(
+            adjusted.hue().into_inner(),
+            lab.hue().into_inner(),
+            epsilon = 0.01
+        );
+        assert_abs_diff_eq!(
+            adjusted.chroma().into_inner(),
+            lab.chroma().into_inner(),
+            epsilon = 0.01
+        );
+    }
+
+    #[test]
+    fn test_adjust_lightness_lab_keeps_hue() {
+        let red = FloatColor::from(BitColor::Red);
+        let hue = LABColor::from(red).hue().into_inner();
+
+        for delta in [-0.3, -0.1, 0.1] {
+            let adjusted = LABColor::from(red.adjust_lightness_lab(SNFloat::new(delta)));
+            assert_abs_diff_eq!(adjusted.hue().into_inner(), hue, epsilon = 0.05);
+        }
+    }
+}
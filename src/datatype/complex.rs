@@ -84,6 +84,43 @@ impl SNComplex {
         SNComplex::new_normalised(self.value + other.into_inner(), normaliser)
     }
 
+    pub fn normalised_sub(self, other: SNComplex, normaliser: SFloatNormaliser) -> SNComplex {
+        SNComplex::new_normalised(self.value - other.into_inner(), normaliser)
+    }
+
+    pub fn normalised_mul(self, other: SNComplex, normaliser: SFloatNormaliser) -> SNComplex {
+        SNComplex::new_normalised(self.value * other.into_inner(), normaliser)
+    }
+
+    /// Returns self unchanged when other is too close to zero to divide by
+    pub fn normalised_div(self, other: SNComplex, normaliser: SFloatNormaliser) -> SNComplex {
+        if other.value.norm() < f64::EPSILON {
+            return self;
+        }
+
+        SNComplex::new_normalised(self.value / other.into_inner(), normaliser)
+    }
+
+    /// The Mobius transformation (az + b) / (cz + d), renormalised into the unit box.
+    /// Returns self unchanged at the pole where cz + d vanishes.
+    pub fn mobius(
+        self,
+        a: SNComplex,
+        b: SNComplex,
+        c: SNComplex,
+        d: SNComplex,
+        normaliser: SFloatNormaliser,
+    ) -> Self {
+        let z = self.value;
+        let denominator = c.value * z + d.value;
+
+        if denominator.norm() < f64::EPSILON {
+            return self;
+        }
+
+        SNComplex::new_normalised((a.value * z + b.value) / denominator, normaliser)
+    }
+
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self::new(Complex::new(
             rng.gen_range(-1.0..=1.0),
@@ -185,14 +222,263 @@ impl<'a> UpdatableRecursively<'a> for SNComplex {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+/// f32 backed counterpart to SNComplex, for coordinate-space code that would otherwise cast constantly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SNComplex32 {
+    value: Complex<f32>,
+}
+
+impl SNComplex32 {
+    pub fn new_unchecked(value: Complex<f32>) -> Self {
+        Self { value }
+    }
+
+    pub fn new(value: Complex<f32>) -> Self {
+        assert!(
+            value.re >= -1.0 && value.re <= 1.0 && value.im >= -1.0 && value.im <= 1.0,
+            "Invalid Complex value: {}",
+            value
+        );
+
+        Self::new_unchecked(value)
+    }
+
+    pub fn new_normalised(value: Complex<f32>, normaliser: SFloatNormaliser) -> Self {
+        Self::from_snfloats(
+            normaliser.normalise(value.re),
+            normaliser.normalise(value.im),
+        )
+    }
+
+    pub fn from_snfloats(x: SNFloat, y: SNFloat) -> Self {
+        Self::new_unchecked(Complex::new(x.into_inner(), y.into_inner()))
+    }
+
+    pub fn from_snpoint(value: SNPoint) -> Self {
+        Self::from_snfloats(value.x(), value.y())
+    }
+
+    pub fn zero() -> Self {
+        Self::ZERO
+    }
+
+    pub fn into_inner(self) -> Complex<f32> {
+        self.value
+    }
+
+    pub fn re(self) -> SNFloat {
+        SNFloat::new_unchecked(self.value.re)
+    }
+
+    pub fn im(self) -> SNFloat {
+        SNFloat::new_unchecked(self.value.im)
+    }
+
+    pub fn to_snpoint(self) -> SNPoint {
+        SNPoint::from_snfloats(self.re(), self.im())
+    }
+
+    pub fn to_angle(self) -> Angle {
+        Angle::new(f32::atan2(self.value.re, self.value.im))
+    }
+
+    pub fn normalised_add(self, other: SNComplex32, normaliser: SFloatNormaliser) -> SNComplex32 {
+        SNComplex32::new_normalised(self.value + other.into_inner(), normaliser)
+    }
+
+    pub fn normalised_sub(self, other: SNComplex32, normaliser: SFloatNormaliser) -> SNComplex32 {
+        SNComplex32::new_normalised(self.value - other.into_inner(), normaliser)
+    }
+
+    pub fn normalised_mul(self, other: SNComplex32, normaliser: SFloatNormaliser) -> SNComplex32 {
+        SNComplex32::new_normalised(self.value * other.into_inner(), normaliser)
+    }
+
+    /// Returns self unchanged when other is too close to zero to divide by
+    pub fn normalised_div(self, other: SNComplex32, normaliser: SFloatNormaliser) -> SNComplex32 {
+        if other.value.norm() < f32::EPSILON {
+            return self;
+        }
+
+        SNComplex32::new_normalised(self.value / other.into_inner(), normaliser)
+    }
+
+    /// The Mobius transformation (az + b) / (cz + d), renormalised into the unit box.
+    /// Returns self unchanged at the pole where cz + d vanishes.
+    pub fn mobius(
+        self,
+        a: SNComplex32,
+        b: SNComplex32,
+        c: SNComplex32,
+        d: SNComplex32,
+        normaliser: SFloatNormaliser,
+    ) -> Self {
+        let z = self.value;
+        let denominator = c.value * z + d.value;
+
+        if denominator.norm() < f32::EPSILON {
+            return self;
+        }
+
+        SNComplex32::new_normalised((a.value * z + b.value) / denominator, normaliser)
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(Complex::new(
+            rng.gen_range(-1.0..=1.0),
+            rng.gen_range(-1.0..=1.0),
+        ))
+    }
+
+    pub fn lerp(self, other: SNComplex32, scalar: UNFloat) -> Self {
+        SNComplex32::new(lerp(self.value, other.value, scalar.into_inner()))
+    }
+
+    pub const ZERO: Self = Self {
+        value: Complex::new(0.0, 0.0),
+    };
+}
+
+impl From<SNComplex> for SNComplex32 {
+    fn from(c: SNComplex) -> Self {
+        Self::new_unchecked(Complex::new(c.value.re as f32, c.value.im as f32))
+    }
+}
+
+impl From<SNComplex32> for SNComplex {
+    fn from(c: SNComplex32) -> Self {
+        Self::new_unchecked(Complex::new(c.value.re as f64, c.value.im as f64))
+    }
+}
+
+impl Serialize for SNComplex32 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SNComplex32 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SNComplex::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl Display for SNComplex32 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.re(), self.im())
+    }
+}
+
+impl Default for SNComplex32 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<'a> Generatable<'a> for SNComplex32 {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for SNComplex32 {
+    type MutArg = ProtoMutArg<'a>;
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
+        *self = Self::random(rng);
+    }
+}
+
+impl<'a> Updatable<'a> for SNComplex32 {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for SNComplex32 {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalised_arithmetic() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let a = SNComplex::random(&mut rng);
+            let b = SNComplex::random(&mut rng);
+
+            let round_trip = a
+                .normalised_add(b, SFloatNormaliser::Sawtooth)
+                .normalised_sub(b, SFloatNormaliser::Sawtooth);
+
+            // Sawtooth wraps at the edges, so compare allowing for a full period either way
+            let wrapped_diff = |x: f64| ((x + 1.0).rem_euclid(2.0) - 1.0).abs();
+            assert!(wrapped_diff(round_trip.value.re - a.value.re) < 1e-5);
+            assert!(wrapped_diff(round_trip.value.im - a.value.im) < 1e-5);
+        }
+
+        let a = SNComplex::new(Complex::new(0.5, 0.25));
+        let b = SNComplex::new(Complex::new(0.0, 1.0));
+
+        let product = a.normalised_mul(b, SFloatNormaliser::Clamp);
+        assert!((product.value - Complex::new(-0.25, 0.5)).norm() < 1e-6);
+        assert!((product.normalised_div(b, SFloatNormaliser::Clamp).value - a.value).norm() < 1e-6);
+        assert_eq!(
+            a.normalised_div(SNComplex::ZERO, SFloatNormaliser::Clamp),
+            a
+        );
+    }
+
     #[test]
     fn test_snpoint_deserialize() {
         let a = SNComplex::new(Complex::new(-0.5, 1.0));
         let b: SNComplex = serde_yaml::from_str(&serde_yaml::to_string(&a).unwrap()).unwrap();
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn test_sncomplex32_round_trip() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let c = SNComplex::random(&mut rng);
+            let c32 = SNComplex32::from(c);
+            let back = SNComplex::from(c32);
+
+            assert!((back.into_inner() - c.into_inner()).norm() < 1e-6);
+            assert_eq!(SNComplex32::from(back), c32);
+        }
+
+        let a = SNComplex32::new(Complex::new(-0.5, 1.0));
+        let b: SNComplex32 = serde_yaml::from_str(&serde_yaml::to_string(&a).unwrap()).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_mobius_identity() {
+        let one = SNComplex::new(Complex::new(1.0, 0.0));
+        let zero = SNComplex::ZERO;
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let z = SNComplex::random(&mut rng);
+            let w = z.mobius(one, zero, zero, one, SFloatNormaliser::Clamp);
+
+            assert!((w.into_inner() - z.into_inner()).norm() < 1e-6);
+        }
+
+        let z = SNComplex::new(Complex::new(0.5, 0.5));
+        assert_eq!(z.mobius(one, one, zero, zero, SFloatNormaliser::Clamp), z);
+    }
 }
@@ -1,4 +1,7 @@
+use std::f32::consts::PI;
+
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{datatype::continuous::*, mutagen_args::*};
@@ -33,6 +36,33 @@ impl SFloatNormaliser {
             Random => SNFloat::new_random_clamped(non_normal_to_default(value)),
         }
     }
+
+    /// Like normalise, but Random draws out of range values from rng instead of the thread rng,
+    /// so that seeded callers stay reproducible
+    pub fn normalise_rng<R: Rng + ?Sized>(self, value: f32, rng: &mut R) -> SNFloat {
+        let value = non_normal_to_default(value);
+
+        match self {
+            SFloatNormaliser::Random if !(-1.0..=1.0).contains(&value) => SNFloat::random(rng),
+            _ => self.normalise(value),
+        }
+    }
+
+    /// Like normalise, but reads periodic normalisers from the lookup table.
+    /// Non-periodic normalisers are computed directly.
+    pub fn normalise_lut(self, value: f32, lut: &NormaliserLut) -> SNFloat {
+        use SFloatNormaliser::*;
+
+        let value = non_normal_to_default(value);
+
+        match self {
+            Sawtooth => SNFloat::new_unchecked(lut.s_sawtooth.get(value)),
+            Triangle => SNFloat::new_unchecked(lut.s_triangle.get(value)),
+            Sin => SNFloat::new_unchecked(lut.s_sin.get(value)),
+            SinRepeating => SNFloat::new_unchecked(lut.s_sin_repeating.get(value)),
+            TanH | Clamp | Fractional | Random => self.normalise(value),
+        }
+    }
 }
 
 impl<'a> Updatable<'a> for SFloatNormaliser {
@@ -68,6 +98,22 @@ impl UFloatNormaliser {
             Random => UNFloat::new_random_clamped(non_normal_to_default(value)),
         }
     }
+
+    /// Like normalise, but reads periodic normalisers from the lookup table.
+    /// Non-periodic normalisers are computed directly.
+    pub fn normalise_lut(self, value: f32, lut: &NormaliserLut) -> UNFloat {
+        use UFloatNormaliser::*;
+
+        let value = non_normal_to_default(value);
+
+        match self {
+            Sawtooth => UNFloat::new_unchecked(lut.u_sawtooth.get(value)),
+            Triangle => UNFloat::new_unchecked(lut.u_triangle.get(value)),
+            Sin => UNFloat::new_unchecked(lut.u_sin.get(value)),
+            SinRepeating => UNFloat::new_unchecked(lut.u_sin_repeating.get(value)),
+            Clamp | Random => self.normalise(value),
+        }
+    }
 }
 
 impl<'a> Updatable<'a> for UFloatNormaliser {
@@ -76,6 +122,66 @@ impl<'a> Updatable<'a> for UFloatNormaliser {
     fn update(&mut self, mut _arg: ProtoUpdArg<'a>) {}
 }
 
+/// Precomputed periodic normaliser transfer functions, for normalising whole buffers cheaply.
+/// Each table holds resolution samples over one period, read back without interpolation.
+pub struct NormaliserLut {
+    s_sawtooth: LutTable,
+    s_triangle: LutTable,
+    s_sin: LutTable,
+    s_sin_repeating: LutTable,
+    u_sawtooth: LutTable,
+    u_triangle: LutTable,
+    u_sin: LutTable,
+    u_sin_repeating: LutTable,
+}
+
+impl NormaliserLut {
+    pub fn new(resolution: usize) -> Self {
+        assert!(resolution > 0);
+
+        Self {
+            s_sawtooth: LutTable::new(resolution, 2.0, |v| SNFloat::new_sawtooth(v).into_inner()),
+            s_triangle: LutTable::new(resolution, 4.0, |v| SNFloat::new_triangle(v).into_inner()),
+            s_sin: LutTable::new(resolution, 4.0 * PI * PI, |v| {
+                SNFloat::new_sin(v).into_inner()
+            }),
+            s_sin_repeating: LutTable::new(resolution, 2.0, |v| {
+                SNFloat::new_sin_repeating(v).into_inner()
+            }),
+            u_sawtooth: LutTable::new(resolution, 1.0, |v| UNFloat::new_sawtooth(v).into_inner()),
+            u_triangle: LutTable::new(resolution, 2.0, |v| UNFloat::new_triangle(v).into_inner()),
+            u_sin: LutTable::new(resolution, 2.0, |v| UNFloat::new_sin(v).into_inner()),
+            u_sin_repeating: LutTable::new(resolution, 1.0, |v| {
+                UNFloat::new_sin_repeating(v).into_inner()
+            }),
+        }
+    }
+}
+
+struct LutTable {
+    period: f32,
+    values: Vec<f32>,
+}
+
+impl LutTable {
+    fn new(resolution: usize, period: f32, f: impl Fn(f32) -> f32) -> Self {
+        Self {
+            period,
+            values: (0..resolution)
+                .map(|i| f(i as f32 * period / resolution as f32))
+                .collect(),
+        }
+    }
+
+    // Rounds down rather than interpolating, so sawtooth discontinuities stay sharp
+    fn get(&self, value: f32) -> f32 {
+        let resolution = self.values.len();
+        let phase = value.rem_euclid(self.period) / self.period;
+
+        self.values[((phase * resolution as f32) as usize).min(resolution - 1)]
+    }
+}
+
 fn non_normal_to_default(value: f32) -> f32 {
     if value.is_normal() {
         value
@@ -83,3 +189,65 @@ fn non_normal_to_default(value: f32) -> f32 {
         f32::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lut_matches_direct() {
+        let resolution = 4096;
+        let lut = NormaliserLut::new(resolution);
+
+        // Each tolerance is the steepest slope times the table spacing, with a little float slack
+        let s_cases = [
+            (SFloatNormaliser::Sawtooth, 2.0),
+            (SFloatNormaliser::Triangle, 4.0),
+            (SFloatNormaliser::Sin, 2.0 * PI),
+            (SFloatNormaliser::SinRepeating, 2.0 * PI),
+            (SFloatNormaliser::TanH, 0.0),
+            (SFloatNormaliser::Clamp, 0.0),
+        ];
+        let u_cases = [
+            (UFloatNormaliser::Sawtooth, 1.0),
+            (UFloatNormaliser::Triangle, 2.0),
+            (UFloatNormaliser::Sin, PI),
+            (UFloatNormaliser::SinRepeating, PI),
+            (UFloatNormaliser::Clamp, 0.0),
+        ];
+
+        for i in -2000..2000 {
+            let value = i as f32 * 0.0173;
+
+            for (normaliser, spread) in s_cases {
+                let tolerance = spread / resolution as f32 + 1e-4;
+                let direct = normaliser.normalise(value).into_inner();
+                let lut = normaliser.normalise_lut(value, &lut).into_inner();
+
+                assert!(
+                    (direct - lut).abs() <= tolerance,
+                    "{:?} at {}: {} vs {}",
+                    normaliser,
+                    value,
+                    direct,
+                    lut
+                );
+            }
+
+            for (normaliser, spread) in u_cases {
+                let tolerance = spread / resolution as f32 + 1e-4;
+                let direct = normaliser.normalise(value).into_inner();
+                let lut = normaliser.normalise_lut(value, &lut).into_inner();
+
+                assert!(
+                    (direct - lut).abs() <= tolerance,
+                    "{:?} at {}: {} vs {}",
+                    normaliser,
+                    value,
+                    direct,
+                    lut
+                );
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::{
     f32::consts::PI,
     fmt::{self, Display, Formatter},
     ops::{Add, AddAssign, Sub, SubAssign},
+    str::FromStr,
 };
 
+use failure::{ensure, Error};
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -51,6 +53,10 @@ impl UNFloat {
         self.value
     }
 
+    pub fn approx_eq(self, other: Self, epsilon: f32) -> bool {
+        (self.value - other.value).abs() <= epsilon
+    }
+
     pub fn average(self, other: Self) -> Self {
         Self::new((self.into_inner() + other.into_inner()) * 0.5)
     }
@@ -128,6 +134,22 @@ impl UNFloat {
     }
 }
 
+impl FromStr for UNFloat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = f32::from_str(s.trim())?;
+
+        ensure!(
+            (0.0..=1.0).contains(&value),
+            "UNFloat must be within [0, 1], got {}",
+            value
+        );
+
+        Ok(Self::new(value))
+    }
+}
+
 impl<'a> Generatable<'a> for UNFloat {
     type GenArg = ProtoGenArg<'a>;
 
@@ -153,6 +175,20 @@ impl<'a> UpdatableRecursively<'a> for UNFloat {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for UNFloat {
+    fn max_value() -> Self {
+        Self::ONE
+    }
+
+    fn min_value() -> Self {
+        Self::ZERO
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
 pub struct SNFloat {
     value: f32,
@@ -238,6 +274,10 @@ impl SNFloat {
         self.value
     }
 
+    pub fn approx_eq(self, other: Self, epsilon: f32) -> bool {
+        (self.value - other.value).abs() <= epsilon
+    }
+
     pub fn to_angle(self) -> Angle {
         Angle::new_from_range(self.value, -1.0, 1.0)
     }
@@ -301,6 +341,22 @@ impl SNFloat {
     pub const NEG_ONE: Self = Self { value: -1.0 };
 }
 
+impl FromStr for SNFloat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = f32::from_str(s.trim())?;
+
+        ensure!(
+            (-1.0..=1.0).contains(&value),
+            "SNFloat must be within [-1, 1], got {}",
+            value
+        );
+
+        Ok(Self::new(value))
+    }
+}
+
 impl Display for SNFloat {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{:.4}", self.into_inner())
@@ -332,6 +388,20 @@ impl<'a> UpdatableRecursively<'a> for SNFloat {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for SNFloat {
+    fn max_value() -> Self {
+        Self::ONE
+    }
+
+    fn min_value() -> Self {
+        Self::NEG_ONE
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
 pub struct Angle {
     value: f32,
@@ -361,12 +431,50 @@ impl Angle {
         Self::new_unchecked(normalised)
     }
 
+    /// Wraps any value into [-PI, PI), without the half turn offset that Angle::new applies
+    pub fn new_wrapped(value: f32) -> Self {
+        Self::new_unchecked((value + PI).rem_euclid(2.0 * PI) - PI)
+    }
+
+    /// Rotates by other, wrapped into [-PI, PI)
     pub fn add(self, other: Self) -> Self {
-        Self::new(self.value + other.value)
+        Self::new_wrapped(self.value + other.value)
     }
 
     pub fn average(self, other: Self) -> Self {
-        Self::new((self.into_inner() + other.into_inner()) * 0.5)
+        Self::circular_mean(&[self, other])
+    }
+
+    /// Mean direction of the angles, found by summing unit vectors so it behaves across the wraparound
+    pub fn circular_mean(angles: &[Angle]) -> Self {
+        let (sin, cos) = angles.iter().fold((0.0, 0.0), |(sin, cos), angle| {
+            (sin + angle.value.sin(), cos + angle.value.cos())
+        });
+
+        Self::new_unchecked(sin.atan2(cos))
+    }
+
+    /// Total rotation of the angles, by repeated add
+    pub fn sum(angles: &[Angle]) -> Self {
+        angles
+            .iter()
+            .fold(Self::ZERO, |total, &angle| total.add(angle))
+    }
+
+    /// Circular mean where each angle's unit vector is scaled by its weight
+    pub fn weighted_blend(angles: &[(Angle, UNFloat)]) -> Self {
+        let (sin, cos) = angles
+            .iter()
+            .fold((0.0, 0.0), |(sin, cos), (angle, weight)| {
+                let weight = weight.into_inner();
+
+                (
+                    sin + angle.value.sin() * weight,
+                    cos + angle.value.cos() * weight,
+                )
+            });
+
+        Self::new_unchecked(sin.atan2(cos))
     }
 
     pub fn new_from_range(value: f32, min: f32, max: f32) -> Self {
@@ -408,6 +516,29 @@ impl Angle {
     }
 }
 
+/// Parses radians, or degrees when suffixed with deg or °, wrapping the result into [-PI, PI)
+impl FromStr for Angle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let (number, to_radians) = if let Some(number) = s.strip_suffix("deg") {
+            (number, PI / 180.0)
+        } else if let Some(number) = s.strip_suffix('°') {
+            (number, PI / 180.0)
+        } else {
+            (s.strip_suffix("rad").unwrap_or(s), 1.0)
+        };
+
+        let value = f32::from_str(number.trim())?;
+
+        ensure!(value.is_finite(), "Angle must be finite, got {}", value);
+
+        Ok(Self::new_wrapped(value * to_radians))
+    }
+}
+
 impl Add<Angle> for Angle {
     type Output = Angle;
 
@@ -461,6 +592,144 @@ impl<'a> UpdatableRecursively<'a> for Angle {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for Angle {
+    fn max_value() -> Self {
+        Self::new_unchecked(PI)
+    }
+
+    fn min_value() -> Self {
+        Self::new_unchecked(-PI)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
+/// A UNFloat that serializes as a BITS-wide integer level rather than a full float, for compact
+/// storage of large genomes. Use BITS = 4 for Nibble precision and BITS = 8 for Byte precision.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct QuantizedUNFloat<const BITS: u8> {
+    value: UNFloat,
+}
+
+impl<const BITS: u8> QuantizedUNFloat<BITS> {
+    const VALID_BITS: () = assert!(BITS >= 1 && BITS <= 8, "BITS must be between 1 and 8");
+
+    /// The highest level, which maps to 1.0
+    pub const MAX_LEVEL: u8 = {
+        let () = Self::VALID_BITS;
+        ((1u16 << BITS) - 1) as u8
+    };
+
+    pub fn new(value: UNFloat) -> Self {
+        let () = Self::VALID_BITS;
+        Self { value }
+    }
+
+    pub fn into_inner(self) -> UNFloat {
+        self.value
+    }
+
+    pub fn to_level(self) -> u8 {
+        (self.value.into_inner() * f32::from(Self::MAX_LEVEL)).round() as u8
+    }
+
+    pub fn from_level(level: u8) -> Self {
+        Self::new(UNFloat::new_clamped(
+            f32::from(level) / f32::from(Self::MAX_LEVEL),
+        ))
+    }
+
+    /// The largest difference between a value and its round-tripped value
+    pub fn max_error() -> f32 {
+        0.5 / f32::from(Self::MAX_LEVEL)
+    }
+}
+
+impl<const BITS: u8> From<UNFloat> for QuantizedUNFloat<BITS> {
+    fn from(value: UNFloat) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const BITS: u8> From<QuantizedUNFloat<BITS>> for UNFloat {
+    fn from(value: QuantizedUNFloat<BITS>) -> Self {
+        value.value
+    }
+}
+
+impl<const BITS: u8> Serialize for QuantizedUNFloat<BITS> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_level().serialize(serializer)
+    }
+}
+
+impl<'de, const BITS: u8> Deserialize<'de> for QuantizedUNFloat<BITS> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let level = u8::deserialize(deserializer)?;
+
+        if level > Self::MAX_LEVEL {
+            return Err(serde::de::Error::custom(format!(
+                "Quantization level {} out of range for {} bits",
+                level, BITS
+            )));
+        }
+
+        Ok(Self::from_level(level))
+    }
+}
+
+/// A SNFloat that serializes as a BITS-wide integer level, see QuantizedUNFloat
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct QuantizedSNFloat<const BITS: u8> {
+    value: SNFloat,
+}
+
+impl<const BITS: u8> QuantizedSNFloat<BITS> {
+    pub fn new(value: SNFloat) -> Self {
+        let () = QuantizedUNFloat::<BITS>::VALID_BITS;
+        Self { value }
+    }
+
+    pub fn into_inner(self) -> SNFloat {
+        self.value
+    }
+
+    /// The largest difference between a value and its round-tripped value
+    pub fn max_error() -> f32 {
+        2.0 * QuantizedUNFloat::<BITS>::max_error()
+    }
+}
+
+impl<const BITS: u8> From<SNFloat> for QuantizedSNFloat<BITS> {
+    fn from(value: SNFloat) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const BITS: u8> From<QuantizedSNFloat<BITS>> for SNFloat {
+    fn from(value: QuantizedSNFloat<BITS>) -> Self {
+        value.value
+    }
+}
+
+impl<const BITS: u8> Serialize for QuantizedSNFloat<BITS> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuantizedUNFloat::<BITS>::new(self.value.to_unsigned()).serialize(serializer)
+    }
+}
+
+impl<'de, const BITS: u8> Deserialize<'de> for QuantizedSNFloat<BITS> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(
+            QuantizedUNFloat::<BITS>::deserialize(deserializer)?
+                .into_inner()
+                .to_signed(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -474,6 +743,156 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_angle_new_wrapped() {
+        assert_relative_eq!(Angle::new_wrapped(0.5).into_inner(), 0.5);
+        assert_relative_eq!(
+            Angle::new_wrapped(0.5 + 2.0 * PI).into_inner(),
+            0.5,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::new_wrapped(-0.5 - 4.0 * PI).into_inner(),
+            -0.5,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(Angle::new_wrapped(PI).into_inner(), -PI);
+    }
+
+    #[test]
+    fn test_quantized_round_trip() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let value = UNFloat::random(&mut rng);
+
+            let nibble: QuantizedUNFloat<4> = value.into();
+            let serialized = serde_json::to_string(&nibble).unwrap();
+            assert!(serialized.parse::<u8>().unwrap() < 16);
+            let recovered: UNFloat = serde_json::from_str::<QuantizedUNFloat<4>>(&serialized)
+                .unwrap()
+                .into();
+            assert!(value.approx_eq(recovered, QuantizedUNFloat::<4>::max_error() + 1e-6));
+
+            let signed = value.to_signed();
+            let byte = QuantizedSNFloat::<8>::from(signed);
+            let recovered: SNFloat =
+                serde_json::from_str::<QuantizedSNFloat<8>>(&serde_json::to_string(&byte).unwrap())
+                    .unwrap()
+                    .into();
+            assert!(signed.approx_eq(recovered, QuantizedSNFloat::<8>::max_error() + 1e-6));
+        }
+
+        assert_eq!(
+            serde_json::to_string(&QuantizedUNFloat::<8>::new(UNFloat::ONE)).unwrap(),
+            "255"
+        );
+        assert!(serde_json::from_str::<QuantizedUNFloat<4>>("16").is_err());
+        assert_eq!(
+            QuantizedSNFloat::<4>::new(SNFloat::NEG_ONE).into_inner(),
+            SNFloat::NEG_ONE
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(UNFloat::from_str("0.25").unwrap(), UNFloat::new(0.25));
+        assert_eq!(SNFloat::from_str(" -0.5 ").unwrap(), SNFloat::new(-0.5));
+        assert!(UNFloat::from_str("-0.1").is_err());
+        assert!(UNFloat::from_str("1.5").is_err());
+        assert!(SNFloat::from_str("-1.01").is_err());
+        assert!(SNFloat::from_str("half").is_err());
+
+        assert_relative_eq!(
+            Angle::from_str("1.5").unwrap().into_inner(),
+            1.5,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::from_str("1.5rad").unwrap().into_inner(),
+            1.5,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::from_str("90deg").unwrap().into_inner(),
+            PI / 2.0,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::from_str("-90 °").unwrap().into_inner(),
+            -PI / 2.0,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::from_str("270°").unwrap().into_inner(),
+            -PI / 2.0,
+            epsilon = 1e-5
+        );
+        assert!(Angle::from_str("NaN").is_err());
+        assert!(Angle::from_str("90 degrees").is_err());
+    }
+
+    #[test]
+    fn test_angle_add() {
+        let quarter = Angle::new_unchecked(PI / 2.0);
+
+        assert_relative_eq!(Angle::ZERO.add(Angle::ZERO).into_inner(), 0.0);
+        assert_relative_eq!(quarter.add(quarter).into_inner(), -PI, epsilon = 1e-5);
+        assert_relative_eq!(
+            quarter.add(Angle::new_unchecked(-PI)).into_inner(),
+            -PI / 2.0,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::sum(&[quarter]).into_inner(),
+            Angle::ZERO.add(quarter).into_inner()
+        );
+    }
+
+    #[test]
+    fn test_angle_sum() {
+        let quarter = Angle::new_unchecked(PI / 2.0);
+
+        assert_relative_eq!(Angle::sum(&[quarter; 4]).into_inner(), 0.0, epsilon = 1e-5);
+        assert_relative_eq!(Angle::sum(&[]).into_inner(), 0.0);
+    }
+
+    #[test]
+    fn test_angle_weighted_blend() {
+        let a = Angle::new_unchecked(0.0);
+        let b = Angle::new_unchecked(PI / 2.0);
+
+        assert_relative_eq!(
+            Angle::weighted_blend(&[(a, UNFloat::ONE), (b, UNFloat::ONE)]).into_inner(),
+            PI / 4.0,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Angle::weighted_blend(&[(a, UNFloat::ONE), (b, UNFloat::ZERO)]).into_inner(),
+            0.0,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_angle_circular_mean() {
+        let a = Angle::new_unchecked(170f32.to_radians());
+        let b = Angle::new_unchecked(-170f32.to_radians());
+
+        assert_relative_eq!(a.average(b).into_inner().abs(), PI, epsilon = 1e-5);
+        assert_relative_eq!(
+            Angle::circular_mean(&[a, b, a, b]).into_inner().abs(),
+            PI,
+            epsilon = 1e-5
+        );
+
+        let c = Angle::new_unchecked(0.25);
+        let d = Angle::new_unchecked(0.75);
+
+        assert_relative_eq!(c.average(d).into_inner(), 0.5, epsilon = 1e-5);
+        assert_eq!(Angle::circular_mean(&[]), Angle::ZERO);
+    }
+
     #[test]
     fn test_sign_conversions() {
         let n = 100_000;
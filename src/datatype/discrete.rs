@@ -4,9 +4,9 @@ use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::mutagen_args::*;
+use crate::{datatype::continuous::*, mutagen_args::*, traits::ranged::*};
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Boolean {
     pub value: bool,
 }
@@ -54,6 +54,24 @@ impl<'a> UpdatableRecursively<'a> for Boolean {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for Boolean {
+    fn max_value() -> Self {
+        Self::new(true)
+    }
+
+    fn min_value() -> Self {
+        Self::new(false)
+    }
+
+    fn to_f64(self) -> f64 {
+        if self.into_inner() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct Nibble {
     pub value: u8,
@@ -93,6 +111,10 @@ impl Nibble {
         Self::new_circular(self.value * other.value)
     }
 
+    pub fn saturating_multiply(self, other: Self) -> Self {
+        Self::new_unchecked((self.value * other.value).min(Self::MODULUS - 1))
+    }
+
     pub fn modulus(self, other: Self) -> Self {
         if other.value == 0 {
             other
@@ -105,6 +127,18 @@ impl Nibble {
         Nibble::new_unchecked(rng.gen_range(0..Self::MODULUS))
     }
 
+    /// Steps circularly up or down by a geometrically distributed amount, where decay is the
+    /// chance of stopping at each step. High decay keeps changes small.
+    pub fn mutate_local<R: Rng + ?Sized>(&mut self, rng: &mut R, decay: UNFloat) {
+        let step = geometric_step(rng, decay, Self::MODULUS as u32 - 1) as u8;
+
+        *self = if rng.gen() {
+            Self::new_circular(self.value + step)
+        } else {
+            Self::new_circular(self.value + Self::MODULUS - step)
+        };
+    }
+
     pub const MODULUS: u8 = 16;
 }
 
@@ -118,11 +152,12 @@ impl<'a> Generatable<'a> for Nibble {
 
 impl<'a> Mutatable<'a> for Nibble {
     type MutArg = ProtoMutArg<'a>;
-    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
-        match rng.gen_range(0..3) {
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        match rng.gen_range(0..4) {
             0 => *self = Self::new(self.into_inner().saturating_add(1) % 16),
             1 => *self = Self::new(self.into_inner().saturating_sub(1) % 16), //TODO: This won't wrap equally in both directiosn. Fix pls
             2 => *self = Self::random(rng),
+            3 => self.mutate_local(rng, arg.decay()),
             _ => unreachable!(),
         }
     }
@@ -138,6 +173,20 @@ impl<'a> UpdatableRecursively<'a> for Nibble {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for Nibble {
+    fn max_value() -> Self {
+        Self::new(Self::MODULUS - 1)
+    }
+
+    fn min_value() -> Self {
+        Self::new(0)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Byte {
     pub value: Wrapping<u8>,
@@ -182,6 +231,10 @@ impl Byte {
         Self::new((self.value * other.value).0)
     }
 
+    pub fn saturating_multiply(self, other: Self) -> Self {
+        Self::new(self.into_inner().saturating_mul(other.into_inner()))
+    }
+
     pub fn modulus(self, other: Self) -> Self {
         if other.value.0 == 0 {
             Self::new(other.value.0)
@@ -193,6 +246,26 @@ impl Byte {
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self { value: rng.gen() }
     }
+
+    /// Steps circularly up or down by a geometrically distributed amount, where decay is the
+    /// chance of stopping at each step. High decay keeps changes small.
+    pub fn mutate_local<R: Rng + ?Sized>(&mut self, rng: &mut R, decay: UNFloat) {
+        let step = geometric_step(rng, decay, 255) as i32;
+
+        *self = self.circular_add_i32(if rng.gen() { step } else { -step });
+    }
+}
+
+/// Counts trials until one succeeds with probability p, from 1 up to max
+fn geometric_step<R: Rng + ?Sized>(rng: &mut R, p: UNFloat, max: u32) -> u32 {
+    let p = f64::from(p.into_inner());
+    let mut step = 1;
+
+    while step < max && !rng.gen_bool(p) {
+        step += 1;
+    }
+
+    step
 }
 
 impl<'a> Generatable<'a> for Byte {
@@ -205,13 +278,14 @@ impl<'a> Generatable<'a> for Byte {
 
 impl<'a> Mutatable<'a> for Byte {
     type MutArg = ProtoMutArg<'a>;
-    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
-        match rng.gen_range(0..4) {
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        match rng.gen_range(0..6) {
             0 => *self = Self::new(self.into_inner().wrapping_add(1)),
             1 => *self = Self::new(self.into_inner().wrapping_sub(1)),
             2 => *self = Self::new(self.into_inner().saturating_add(1)),
             3 => *self = Self::new(self.into_inner().saturating_sub(1)),
             4 => *self = Self::random(rng),
+            5 => self.mutate_local(rng, arg.decay()),
             _ => unreachable!(),
         }
     }
@@ -227,6 +301,20 @@ impl<'a> UpdatableRecursively<'a> for Byte {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for Byte {
+    fn max_value() -> Self {
+        Self::new(u8::MAX)
+    }
+
+    fn min_value() -> Self {
+        Self::new(0)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
 pub struct UInt {
     pub value: Wrapping<u32>,
@@ -267,6 +355,14 @@ impl UInt {
         }
     }
 
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.into_inner().min(other.into_inner()))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.into_inner().max(other.into_inner()))
+    }
+
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self { value: rng.gen() }
     }
@@ -297,6 +393,20 @@ impl<'a> UpdatableRecursively<'a> for UInt {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
+impl Ranged for UInt {
+    fn max_value() -> Self {
+        Self::new(u32::MAX)
+    }
+
+    fn min_value() -> Self {
+        Self::new(0)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
 pub struct SInt {
     pub value: Wrapping<i32>,
@@ -337,6 +447,19 @@ impl SInt {
         }
     }
 
+    /// Absolute value, saturating i32::MIN to i32::MAX
+    pub fn abs(self) -> Self {
+        Self::new(self.into_inner().saturating_abs())
+    }
+
+    pub fn signum(self) -> Self {
+        Self::new(self.into_inner().signum())
+    }
+
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        Self::new(self.into_inner().clamp(lo.into_inner(), hi.into_inner()))
+    }
+
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self { value: rng.gen() }
     }
@@ -366,3 +489,190 @@ impl<'a> Updatable<'a> for SInt {
 impl<'a> UpdatableRecursively<'a> for SInt {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
+
+impl Ranged for SInt {
+    fn max_value() -> Self {
+        Self::new(i32::MAX)
+    }
+
+    fn min_value() -> Self {
+        Self::new(i32::MIN)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mutagen::Reborrow;
+
+    use crate::util::DeterministicRng;
+
+    #[test]
+    fn test_mutate_local_prefers_small_steps() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let trials = 10_000;
+
+        let distances: Vec<u8> = (0..trials)
+            .map(|_| {
+                let start = Nibble::random(&mut rng);
+                let mut n = start;
+                n.mutate_local(&mut rng, UNFloat::new(0.9));
+
+                let d = (n.into_inner() + Nibble::MODULUS - start.into_inner()) % Nibble::MODULUS;
+                d.min(Nibble::MODULUS - d)
+            })
+            .collect();
+
+        let fraction = |f: &dyn Fn(u8) -> bool| {
+            distances.iter().filter(|&&d| f(d)).count() as f32 / trials as f32
+        };
+
+        assert_eq!(fraction(&|d| d == 0), 0.0);
+        assert!(fraction(&|d| d == 1) > 0.85);
+        assert!(fraction(&|d| d >= 3) < 0.02);
+
+        let mut byte = Byte::new(0);
+        let mut largest = 0;
+
+        for _ in 0..trials {
+            let start = byte.into_inner();
+            byte.mutate_local(&mut rng, UNFloat::new(0.1));
+
+            let d = byte.into_inner().wrapping_sub(start);
+            largest = largest.max(d.min(d.wrapping_neg()));
+        }
+
+        assert!(largest > 10);
+    }
+
+    #[test]
+    fn test_mutation_intensity_scales_nibble_changes() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let trials = 10_000;
+
+        let mut average_change = |intensity: f32| {
+            let total: u32 = (0..trials)
+                .map(|_| {
+                    let start = Nibble::random(&mut rng);
+                    let mut n = start;
+                    n.mutate_rng(
+                        &mut rng,
+                        ProtoMutArg::with_intensity(&mut None, UNFloat::new(intensity)),
+                    );
+
+                    let d =
+                        (n.into_inner() + Nibble::MODULUS - start.into_inner()) % Nibble::MODULUS;
+                    u32::from(d.min(Nibble::MODULUS - d))
+                })
+                .sum();
+
+            total as f32 / trials as f32
+        };
+
+        let gentle = average_change(0.0);
+        let aggressive = average_change(1.0);
+
+        assert!(aggressive > gentle + 0.1);
+    }
+
+    #[test]
+    fn test_byte_mutation_takes_local_steps() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let trials = 10_000;
+
+        let medium_steps = (0..trials)
+            .filter(|_| {
+                let start = Byte::new(128);
+                let mut b = start;
+                b.mutate_rng(
+                    &mut rng,
+                    ProtoMutArg::with_intensity(&mut None, UNFloat::ONE),
+                );
+
+                (2..=8).contains(&b.into_inner().abs_diff(start.into_inner()))
+            })
+            .count();
+
+        // The fixed and random arms alone land here under 1% of the time
+        assert!(medium_steps as f32 / trials as f32 > 0.04);
+    }
+
+    #[test]
+    fn test_default_mutation_decay() {
+        let mut profiler = None;
+        let mut root = ProtoMutArg::new(&mut profiler);
+
+        assert_eq!(root.decay(), UNFloat::new(0.5));
+        assert_eq!(
+            root.magnitude(),
+            UNFloat::new(ProtoMutArg::DEFAULT_INTENSITY)
+        );
+
+        let root_decay = root.decay();
+        assert!(root.reborrow().decay() < root_decay);
+    }
+
+    #[test]
+    fn test_saturating_multiply() {
+        assert_eq!(
+            Byte::new(200)
+                .saturating_multiply(Byte::new(2))
+                .into_inner(),
+            255
+        );
+        assert_eq!(
+            Byte::new(200).circular_multiply(Byte::new(2)).into_inner(),
+            144
+        );
+        assert_eq!(
+            Byte::new(12).saturating_multiply(Byte::new(3)).into_inner(),
+            36
+        );
+
+        assert_eq!(
+            Nibble::new(5)
+                .saturating_multiply(Nibble::new(4))
+                .into_inner(),
+            15
+        );
+        assert_eq!(
+            Nibble::new(5)
+                .circular_multiply(Nibble::new(4))
+                .into_inner(),
+            4
+        );
+        assert_eq!(
+            Nibble::new(3)
+                .saturating_multiply(Nibble::new(4))
+                .into_inner(),
+            12
+        );
+    }
+
+    #[test]
+    fn test_sint_helpers() {
+        assert_eq!(SInt::new(-5).abs().into_inner(), 5);
+        assert_eq!(SInt::new(i32::MIN).abs().into_inner(), i32::MAX);
+        assert_eq!(SInt::new(i32::MIN).signum().into_inner(), -1);
+        assert_eq!(SInt::new(0).signum().into_inner(), 0);
+        assert_eq!(SInt::new(42).signum().into_inner(), 1);
+
+        let (lo, hi) = (SInt::new(-10), SInt::new(10));
+
+        assert_eq!(SInt::new(i32::MIN).clamp(lo, hi).into_inner(), -10);
+        assert_eq!(SInt::new(3).clamp(lo, hi).into_inner(), 3);
+        assert_eq!(SInt::new(i32::MAX).clamp(lo, hi).into_inner(), 10);
+    }
+
+    #[test]
+    fn test_uint_min_max() {
+        let (a, b) = (UInt::new(3), UInt::new(u32::MAX));
+
+        assert_eq!(a.min(b).into_inner(), 3);
+        assert_eq!(a.max(b).into_inner(), u32::MAX);
+    }
+}
@@ -14,7 +14,10 @@ pub enum DistanceFunction {
     Manhattan,
     Chebyshev,
     Minimum,
-    //Minkowski,
+    /// Exponent mapped from [0, 1] to [1, 8], so it runs from Manhattan towards Chebyshev
+    Minkowski {
+        p: UNFloat,
+    },
 }
 
 //wrapped in triangle waves for now, maybe parametrise SN resolution method
@@ -31,9 +34,19 @@ impl DistanceFunction {
             Manhattan => (x.abs() + y.abs()) * 0.5,
             Chebyshev => (x.abs()).max(y.abs()),
             Minimum => (x.abs()).min(y.abs()),
+            Minkowski { p } => {
+                let p = Self::minkowski_exponent(p);
+
+                // Scaled by 2^(-1/p) so that p = 1 matches the halved Manhattan distance
+                ((x.abs().powf(p) + y.abs().powf(p)) * 0.5).powf(1.0 / p)
+            }
         }
     }
 
+    pub fn minkowski_exponent(p: UNFloat) -> f32 {
+        1.0 + 7.0 * p.into_inner()
+    }
+
     pub fn calculate_normalised(
         self,
         a: SNPoint,
@@ -44,11 +57,14 @@ impl DistanceFunction {
     }
 
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
-        match rng.gen_range(0..4) {
+        match rng.gen_range(0..5) {
             0 => DistanceFunction::Euclidean,
             1 => DistanceFunction::Manhattan,
             2 => DistanceFunction::Chebyshev,
             3 => DistanceFunction::Minimum,
+            4 => DistanceFunction::Minkowski {
+                p: UNFloat::random(rng),
+            },
             _ => unreachable!(),
         }
     }
@@ -64,8 +80,11 @@ impl<'a> Generatable<'a> for DistanceFunction {
 
 impl<'a> Mutatable<'a> for DistanceFunction {
     type MutArg = ProtoMutArg<'a>;
-    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
-        *self = Self::random(rng);
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        match self {
+            DistanceFunction::Minkowski { p } if rng.gen::<bool>() => p.mutate_rng(rng, arg),
+            _ => *self = Self::random(rng),
+        }
     }
 }
 
@@ -74,3 +93,40 @@ impl<'a> Updatable<'a> for DistanceFunction {
 
     fn update(&mut self, _arg: Self::UpdateArg) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    use crate::util::DeterministicRng;
+
+    #[test]
+    fn test_minkowski_limits() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let at = |f: DistanceFunction, a: SNPoint, b: SNPoint| {
+            f.calculate_point2(a.into_inner(), b.into_inner())
+        };
+
+        for _ in 0..1000 {
+            let (a, b) = (SNPoint::random(&mut rng), SNPoint::random(&mut rng));
+
+            let manhattan = at(DistanceFunction::Manhattan, a, b);
+            let chebyshev = at(DistanceFunction::Chebyshev, a, b);
+            let low = at(DistanceFunction::Minkowski { p: UNFloat::ZERO }, a, b);
+            let mid = at(
+                DistanceFunction::Minkowski {
+                    p: UNFloat::new(1.0 / 7.0),
+                },
+                a,
+                b,
+            );
+            let high = at(DistanceFunction::Minkowski { p: UNFloat::ONE }, a, b);
+
+            assert!((low - manhattan).abs() < 1e-5);
+            assert!((high - chebyshev).abs() <= 0.1 * chebyshev + 1e-6);
+            assert!((high - chebyshev).abs() <= (mid - chebyshev).abs() + 1e-6);
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use std::f64::consts::PI;
+
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use nalgebra::Complex;
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
@@ -34,3 +37,92 @@ impl<'a> Updatable<'a> for IterativeResult {
 
     fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
+
+/// An animated Julia set. Each update turns t by a degree, orbiting c around c_drift.
+#[derive(Generatable, Mutatable, Deserialize, Serialize, Clone, Copy, Debug)]
+#[mutagen(gen_arg = type ProtoGenArg<'a>, mut_arg = type ProtoMutArg<'a>)]
+pub struct JuliaField {
+    pub c: SNComplex,
+    pub c_drift: SNComplex,
+    pub max_iter: Byte,
+    pub t: Angle,
+}
+
+impl JuliaField {
+    const STEP: f64 = PI / 180.0;
+
+    /// c rotated by t around c_drift, clamped back into range
+    pub fn current_c(&self) -> SNComplex {
+        let offset = self.c.into_inner() - self.c_drift.into_inner();
+        let rotation = Complex::from_polar(1.0, f64::from(self.t.into_inner()));
+
+        SNComplex::new_normalised(
+            self.c_drift.into_inner() + offset * rotation,
+            SFloatNormaliser::Clamp,
+        )
+    }
+
+    /// Iterates z = z^2 + c from p, scaled to span [-2, 2], until z escapes radius 2 or max_iter
+    /// is reached
+    pub fn sample(&self, p: SNPoint, normaliser: SFloatNormaliser) -> IterativeResult {
+        let c = self.current_c().into_inner();
+        let mut z = SNComplex::from_snpoint(p).into_inner() * 2.0;
+        let mut iter = 0;
+
+        while iter < self.max_iter.into_inner() && z.norm_sqr() <= 4.0 {
+            z = z * z + c;
+            iter += 1;
+        }
+
+        IterativeResult::new(SNComplex::new_normalised(z, normaliser), Byte::new(iter))
+    }
+}
+
+impl<'a> Updatable<'a> for JuliaField {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {
+        self.t = Angle::new_wrapped((f64::from(self.t.into_inner()) + Self::STEP) as f32);
+    }
+}
+
+impl<'a> UpdatableRecursively<'a> for JuliaField {
+    fn update_recursively(&mut self, arg: ProtoUpdArg<'a>) {
+        self.update(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::*;
+
+    #[test]
+    fn test_julia_field_animates() {
+        let mut field = JuliaField {
+            c: SNComplex::new(Complex::new(-0.8, 0.156)),
+            c_drift: SNComplex::zero(),
+            max_iter: Byte::new(255),
+            t: Angle::ZERO,
+        };
+
+        // Just outside the filled set along the real axis
+        let p = SNPoint::new(Point2::new(0.8, 0.0));
+        let before = field.sample(p, SFloatNormaliser::TanH);
+
+        for _ in 0..10 {
+            field.update(ProtoUpdArg {
+                profiler: &mut None,
+            });
+        }
+
+        let after = field.sample(p, SFloatNormaliser::TanH);
+
+        assert!((field.t.into_inner() - 10f32.to_radians()).abs() < 1e-5);
+        assert_ne!(
+            (before.z_final, before.iter_final),
+            (after.z_final, after.iter_final)
+        );
+    }
+}
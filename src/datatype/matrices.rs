@@ -4,9 +4,9 @@ use nalgebra::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::datatype::continuous::*;
+use crate::datatype::{continuous::*, points::*};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct SNFloatMatrix3 {
     value: nalgebra::Matrix3<f32>,
 }
@@ -36,6 +36,67 @@ impl SNFloatMatrix3 {
         }
     }
 
+    /// Translation, then rotation, then scaling, so points are scaled first when transformed
+    pub fn from_trs(translation: SNPoint, rotation: Angle, scale: SNPoint) -> Self {
+        Self::new_translation(translation.x(), translation.y())
+            .multiply(Self::new_rotation(rotation))
+            .multiply(Self::new_scaling(scale.x(), scale.y()))
+    }
+
+    /// Inverse of from_trs, with the x scale taken as non-negative.
+    /// None if the matrix isn't affine, has shear, or any component is out of range.
+    pub fn decompose(&self) -> Option<(SNPoint, Angle, SNPoint)> {
+        const EPSILON: f32 = 1e-5;
+
+        let m = &self.value;
+
+        if m[(2, 0)].abs() > EPSILON
+            || m[(2, 1)].abs() > EPSILON
+            || (m[(2, 2)] - 1.0).abs() > EPSILON
+        {
+            return None;
+        }
+
+        let x_axis = Vector2::new(m[(0, 0)], m[(1, 0)]);
+        let y_axis = Vector2::new(m[(0, 1)], m[(1, 1)]);
+
+        if x_axis.dot(&y_axis).abs() > EPSILON {
+            return None;
+        }
+
+        let (theta, scale_x, scale_y) = if x_axis.norm() > EPSILON {
+            let theta = x_axis.y.atan2(x_axis.x);
+
+            (
+                theta,
+                x_axis.norm(),
+                y_axis.dot(&Vector2::new(-theta.sin(), theta.cos())),
+            )
+        } else {
+            ((-y_axis.x).atan2(y_axis.y), 0.0, y_axis.norm())
+        };
+
+        let in_range = |v: f32| (-1.0 - EPSILON..=1.0 + EPSILON).contains(&v);
+        let translation = (m[(0, 2)], m[(1, 2)]);
+
+        if ![translation.0, translation.1, scale_x, scale_y]
+            .iter()
+            .all(|&v| in_range(v))
+        {
+            return None;
+        }
+
+        let point = |x: f32, y: f32| {
+            SNPoint::from_snfloats(SNFloat::new_clamped(x), SNFloat::new_clamped(y))
+        };
+
+        Some((
+            point(translation.0, translation.1),
+            Angle::new_wrapped(theta),
+            point(scale_x, scale_y),
+        ))
+    }
+
     pub fn multiply(self, other: Self) -> Self {
         Self {
             value: self.into_inner() * other.into_inner(),
@@ -52,3 +113,36 @@ impl SNFloatMatrix3 {
         self.value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn test_trs_round_trip() {
+        let translation = SNPoint::new(Point2::new(0.25, -0.5));
+        let rotation = Angle::new_unchecked(PI / 3.0);
+        let scale = SNPoint::new(Point2::new(0.5, -0.75));
+
+        let (t, r, s) = SNFloatMatrix3::from_trs(translation, rotation, scale)
+            .decompose()
+            .unwrap();
+
+        assert!(t.approx_eq(translation, 1e-5));
+        assert!((r.into_inner() - rotation.into_inner()).abs() < 1e-5);
+        assert!(s.approx_eq(scale, 1e-5));
+
+        let sheared = SNFloatMatrix3::new_shear(SNFloat::new(0.5), SNFloat::ZERO);
+        assert!(sheared.decompose().is_none());
+
+        // Flipping both axes is a half turn, which wraps to -PI like every other Angle
+        let (_, r, s) = SNFloatMatrix3::new_scaling(SNFloat::NEG_ONE, SNFloat::NEG_ONE)
+            .decompose()
+            .unwrap();
+
+        assert_eq!(r.into_inner(), -PI);
+        assert!(s.approx_eq(SNPoint::new(Point2::new(1.0, 1.0)), 1e-5));
+    }
+}
@@ -1,7 +1,8 @@
-use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use failure::{ensure, Fallible};
+use mutagen::{Generatable, Mutatable, Reborrow, Updatable, UpdatableRecursively};
 use noise::{
-    BasicMulti, Billow, Checkerboard, Fbm, HybridMulti, NoiseFn, OpenSimplex, RangeFunction,
-    RidgedMulti, Seedable, SuperSimplex, Value, Worley,
+    BasicMulti, Billow, Checkerboard, Fbm, HybridMulti, MultiFractal, NoiseFn, OpenSimplex,
+    RangeFunction, RidgedMulti, Seedable, SuperSimplex, Value, Worley,
 };
 use rand::prelude::*;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
@@ -21,6 +22,8 @@ pub enum NoiseFunctions {
     SuperSimplex(Noise<SuperSimplex>),
     Value(Noise<Value>),
     Worley(Noise<Worley>),
+    HashValue(Noise<HashValue>),
+    Curve(NoiseCurve),
 }
 
 impl NoiseFunctions {
@@ -36,6 +39,8 @@ impl NoiseFunctions {
             NoiseFunctions::SuperSimplex(noise) => noise.noise.get([x, y, t]),
             NoiseFunctions::Value(noise) => noise.noise.get([x, y, t]),
             NoiseFunctions::Worley(noise) => noise.noise.get([x, y, t]),
+            NoiseFunctions::HashValue(noise) => noise.noise.get([x, y, t]),
+            NoiseFunctions::Curve(curve) => curve.remap(curve.base.compute(x, y, t)),
         }
     }
 }
@@ -47,7 +52,235 @@ impl<'a> Updatable<'a> for NoiseFunctions {
 }
 
 impl<'a> UpdatableRecursively<'a> for NoiseFunctions {
-    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+    fn update_recursively(&mut self, mut arg: ProtoUpdArg<'a>) {
+        if let NoiseFunctions::Curve(curve) = self {
+            curve.base.update_recursively(arg.reborrow());
+        }
+
+        self.update(arg);
+    }
+}
+
+/// Reshapes the output of a base noise through a monotone cubic spline over control points,
+/// like libnoise's Curve module. Inputs outside the control points take the nearest end's output.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(try_from = "NoiseCurveFields")]
+pub struct NoiseCurve {
+    pub base: Box<NoiseFunctions>,
+    control_points: Vec<(SNFloat, SNFloat)>,
+    /// Control points as f64, rebuilt whenever the control points change
+    #[serde(skip)]
+    points: Vec<(f64, f64)>,
+    /// Spline tangent at each of the points
+    #[serde(skip)]
+    tangents: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct NoiseCurveFields {
+    base: Box<NoiseFunctions>,
+    control_points: Vec<(SNFloat, SNFloat)>,
+}
+
+impl TryFrom<NoiseCurveFields> for NoiseCurve {
+    type Error = failure::Error;
+
+    fn try_from(fields: NoiseCurveFields) -> Fallible<Self> {
+        Self::try_new(fields.base, fields.control_points)
+    }
+}
+
+impl NoiseCurve {
+    #[track_caller]
+    pub fn new(base: Box<NoiseFunctions>, control_points: Vec<(SNFloat, SNFloat)>) -> Self {
+        Self::try_new(base, control_points).unwrap()
+    }
+
+    /// Like new, but fails instead of panicking when there are no control points
+    pub fn try_new(
+        base: Box<NoiseFunctions>,
+        control_points: Vec<(SNFloat, SNFloat)>,
+    ) -> Fallible<Self> {
+        ensure!(
+            !control_points.is_empty(),
+            "NoiseCurve needs at least one control point"
+        );
+
+        let mut curve = Self {
+            base,
+            control_points: Vec::new(),
+            points: Vec::new(),
+            tangents: Vec::new(),
+        };
+
+        curve.set_control_points(control_points);
+
+        Ok(curve)
+    }
+
+    /// Replaces the control points and precomputes the spline through them
+    #[track_caller]
+    pub fn set_control_points(&mut self, control_points: Vec<(SNFloat, SNFloat)>) {
+        self.control_points = Self::sorted(control_points);
+        self.points = self
+            .control_points
+            .iter()
+            .map(|(x, y)| (f64::from(x.into_inner()), f64::from(y.into_inner())))
+            .collect();
+        self.tangents = Self::tangents(&self.points);
+    }
+
+    /// Sorts the control points by input, keeping the first of any with equal inputs
+    #[track_caller]
+    fn sorted(mut control_points: Vec<(SNFloat, SNFloat)>) -> Vec<(SNFloat, SNFloat)> {
+        assert!(!control_points.is_empty());
+
+        control_points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        control_points.dedup_by(|b, a| a.0 == b.0);
+
+        control_points
+    }
+
+    pub fn control_points(&self) -> &[(SNFloat, SNFloat)] {
+        &self.control_points
+    }
+
+    /// Fritsch-Carlson tangents, so the curve never overshoots its control points
+    fn tangents(points: &[(f64, f64)]) -> Vec<f64> {
+        if points.len() < 2 {
+            return vec![0.0; points.len()];
+        }
+
+        let secants: Vec<f64> = points
+            .windows(2)
+            .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+            .collect();
+
+        (0..points.len())
+            .map(|i| {
+                if i == 0 {
+                    secants[0]
+                } else if i == secants.len() {
+                    secants[i - 1]
+                } else if secants[i - 1] * secants[i] <= 0.0 {
+                    0.0
+                } else {
+                    // Harmonic mean of the neighbouring secants keeps each segment monotone
+                    2.0 / (1.0 / secants[i - 1] + 1.0 / secants[i])
+                }
+            })
+            .collect()
+    }
+
+    /// Monotone cubic interpolation through the precomputed points and tangents
+    pub fn remap(&self, value: f64) -> f64 {
+        let points = &self.points;
+        let (first, last) = (points[0], points[points.len() - 1]);
+
+        if value <= first.0 {
+            return first.1;
+        } else if value >= last.0 {
+            return last.1;
+        }
+
+        let i = points.windows(2).position(|w| value < w[1].0).unwrap();
+        let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+
+        let h = x1 - x0;
+        let t = (value - x0) / h;
+        let (t2, t3) = (t * t, t * t * t);
+
+        (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+            + (t3 - 2.0 * t2 + t) * h * self.tangents[i]
+            + (-2.0 * t3 + 3.0 * t2) * y1
+            + (t3 - t2) * h * self.tangents[i + 1]
+    }
+
+    fn random_control_points<R: Rng + ?Sized>(rng: &mut R) -> Vec<(SNFloat, SNFloat)> {
+        (0..rng.gen_range(2..=6))
+            .map(|_| (SNFloat::random(rng), SNFloat::random(rng)))
+            .collect()
+    }
+}
+
+impl<'a> Generatable<'a> for NoiseCurve {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, arg: ProtoGenArg<'a>) -> Self {
+        Self::new(
+            Box::new(NoiseFunctions::generate_rng(rng, arg)),
+            Self::random_control_points(rng),
+        )
+    }
+}
+
+impl<'a> Mutatable<'a> for NoiseCurve {
+    type MutArg = ProtoMutArg<'a>;
+
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen() {
+            self.base.mutate_rng(rng, arg);
+        } else {
+            self.set_control_points(Self::random_control_points(rng));
+        }
+    }
+}
+
+/// Noise that carries its own time, advanced by speed on every update
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnimatedNoise {
+    pub inner: NoiseFunctions,
+    pub time: f64,
+    pub speed: UNFloat,
+}
+
+impl AnimatedNoise {
+    pub fn new(inner: NoiseFunctions, speed: UNFloat) -> Self {
+        Self {
+            inner,
+            time: 0.0,
+            speed,
+        }
+    }
+
+    pub fn sample(&self, x: f64, y: f64) -> f64 {
+        self.inner.compute(x, y, self.time)
+    }
+}
+
+impl<'a> Generatable<'a> for AnimatedNoise {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, arg: ProtoGenArg<'a>) -> Self {
+        Self::new(NoiseFunctions::generate_rng(rng, arg), UNFloat::random(rng))
+    }
+}
+
+impl<'a> Mutatable<'a> for AnimatedNoise {
+    type MutArg = ProtoMutArg<'a>;
+
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen() {
+            self.inner.mutate_rng(rng, arg);
+        } else {
+            self.speed = UNFloat::random(rng);
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for AnimatedNoise {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {
+        self.time += f64::from(self.speed.into_inner());
+    }
+}
+
+impl<'a> UpdatableRecursively<'a> for AnimatedNoise {
+    fn update_recursively(&mut self, mut arg: ProtoUpdArg<'a>) {
+        self.inner.update_recursively(arg.reborrow());
+        self.update(arg);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -147,19 +380,79 @@ impl<'a> Mutatable<'a> for SeedParams {
     }
 }
 
+/// Seed and octave settings shared by the fractal noise functions.
+/// Saves from before these settings existed only hold a seed, so missing fields take the defaults.
+#[derive(Serialize, Deserialize, Generatable, Mutatable, Debug, Clone, Copy)]
+#[mutagen(gen_arg = type ProtoGenArg<'a>, mut_arg = type ProtoMutArg<'a>)]
+#[serde(default)]
+pub struct FractalParams {
+    #[serde(flatten)]
+    pub seed: SeedParams,
+    pub octaves: Nibble,
+    pub frequency: UNFloat,
+    pub lacunarity: UNFloat,
+    pub persistence: UNFloat,
+}
+
+impl Default for FractalParams {
+    /// The noise crate's Fbm defaults, which the other fractals mostly share
+    fn default() -> Self {
+        Self {
+            seed: SeedParams {
+                seed: Fbm::DEFAULT_SEED,
+            },
+            octaves: Nibble::new(((Fbm::DEFAULT_OCTAVE_COUNT - 1) * 2) as u8),
+            frequency: UNFloat::new(((Fbm::DEFAULT_FREQUENCY - 0.5) / 3.5) as f32),
+            lacunarity: UNFloat::new(((Fbm::DEFAULT_LACUNARITY - 1.5) / 1.5) as f32),
+            persistence: UNFloat::new(((Fbm::DEFAULT_PERSISTENCE - 0.25) / 0.5) as f32),
+        }
+    }
+}
+
+impl FractalParams {
+    /// 1..=8, as every octave costs another evaluation of the source noise
+    pub fn octave_count(&self) -> usize {
+        usize::from(self.octaves.into_inner() / 2) + 1
+    }
+
+    /// 0.5..=4.0
+    pub fn scaled_frequency(&self) -> f64 {
+        0.5 + f64::from(self.frequency.into_inner()) * 3.5
+    }
+
+    /// 1.5..=3.0
+    pub fn scaled_lacunarity(&self) -> f64 {
+        1.5 + f64::from(self.lacunarity.into_inner()) * 1.5
+    }
+
+    /// 0.25..=0.75
+    pub fn scaled_persistence(&self) -> f64 {
+        0.25 + f64::from(self.persistence.into_inner()) * 0.5
+    }
+
+    fn apply<T: MultiFractal + Seedable>(&self, noise: T) -> T {
+        noise
+            .set_octaves(self.octave_count())
+            .set_frequency(self.scaled_frequency())
+            .set_lacunarity(self.scaled_lacunarity())
+            .set_persistence(self.scaled_persistence())
+            .set_seed(self.seed.seed)
+    }
+}
+
 impl NoiseFunction for BasicMulti {
-    type Params = SeedParams;
+    type Params = FractalParams;
 
     fn new(params: &Self::Params) -> Self {
-        Self::default().set_seed(params.seed)
+        params.apply(Self::default())
     }
 }
 
 impl NoiseFunction for Billow {
-    type Params = SeedParams;
+    type Params = FractalParams;
 
     fn new(params: &Self::Params) -> Self {
-        Self::default().set_seed(params.seed)
+        params.apply(Self::default())
     }
 }
 
@@ -178,18 +471,18 @@ pub struct CheckerboardParams {
 }
 
 impl NoiseFunction for Fbm {
-    type Params = SeedParams;
+    type Params = FractalParams;
 
     fn new(params: &Self::Params) -> Self {
-        Self::default().set_seed(params.seed)
+        params.apply(Self::default())
     }
 }
 
 impl NoiseFunction for HybridMulti {
-    type Params = SeedParams;
+    type Params = FractalParams;
 
     fn new(params: &Self::Params) -> Self {
-        Self::default().set_seed(params.seed)
+        params.apply(Self::default())
     }
 }
 
@@ -205,9 +498,10 @@ impl NoiseFunction for RidgedMulti {
     type Params = RidgedMultiParams;
 
     fn new(params: &Self::Params) -> Self {
-        Self::default()
+        params
+            .fractal
+            .apply(Self::default())
             .set_attenuation(f64::from(params.attenuation.into_inner()) * 8.0)
-            .set_seed(params.seed.seed)
     }
 }
 
@@ -216,7 +510,7 @@ impl NoiseFunction for RidgedMulti {
 pub struct RidgedMultiParams {
     pub attenuation: UNFloat,
     #[serde(flatten)]
-    pub seed: SeedParams,
+    pub fractal: FractalParams,
 }
 
 impl NoiseFunction for SuperSimplex {
@@ -278,3 +572,234 @@ impl From<RangeFunctionParam> for RangeFunction {
         }
     }
 }
+
+/// Value noise over an integer lattice, built only from integer hashing and float arithmetic so
+/// that its output is identical across platforms and versions of the noise crate
+#[derive(Debug, Clone, Copy)]
+pub struct HashValue {
+    seed: u32,
+}
+
+impl HashValue {
+    fn lattice(&self, x: i64, y: i64, z: i64) -> f64 {
+        let hash =
+            wang_hash(self.seed ^ wang_hash(x as u32 ^ wang_hash(y as u32 ^ wang_hash(z as u32))));
+
+        f64::from(hash) / f64::from(u32::MAX) * 2.0 - 1.0
+    }
+}
+
+impl NoiseFunction for HashValue {
+    type Params = SeedParams;
+
+    fn new(params: &Self::Params) -> Self {
+        Self { seed: params.seed }
+    }
+}
+
+impl NoiseFn<[f64; 3]> for HashValue {
+    fn get(&self, point: [f64; 3]) -> f64 {
+        let [x, y, z] = point;
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (tx, ty, tz) = (smooth(x - x0), smooth(y - y0), smooth(z - z0));
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+        let plane = |zi| {
+            lerp(
+                lerp(self.lattice(xi, yi, zi), self.lattice(xi + 1, yi, zi), tx),
+                lerp(
+                    self.lattice(xi, yi + 1, zi),
+                    self.lattice(xi + 1, yi + 1, zi),
+                    tx,
+                ),
+                ty,
+            )
+        };
+
+        lerp(plane(zi), plane(zi + 1), tz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animated_noise_advances() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        let inner = NoiseFunctions::OpenSimplex(Noise::generate_rng(
+            &mut rng,
+            ProtoGenArg {
+                profiler: &mut None,
+            },
+        ));
+        let mut noise = AnimatedNoise::new(inner, UNFloat::new(0.1));
+
+        let mut samples = vec![noise.sample(0.3, 0.7)];
+
+        for _ in 0..10 {
+            noise.update(ProtoUpdArg {
+                profiler: &mut None,
+            });
+            samples.push(noise.sample(0.3, 0.7));
+        }
+
+        assert!((noise.time - 1.0).abs() < 1e-6);
+        assert!(samples.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn test_hash_value_noise_is_deterministic() {
+        let noise = HashValue::new(&SeedParams { seed: 1234 });
+        let other_seed = HashValue::new(&SeedParams { seed: 4321 });
+
+        for &(x, y, t) in &[(0.0, 0.0, 0.0), (0.3, -2.7, 5.5), (-101.25, 17.5, 0.125)] {
+            let value = noise.get([x, y, t]);
+
+            assert_eq!(
+                value,
+                HashValue::new(&SeedParams { seed: 1234 }).get([x, y, t])
+            );
+            assert_ne!(value, other_seed.get([x, y, t]));
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_fractal_params_shape_noise() {
+        let params = |octaves, frequency| FractalParams {
+            seed: SeedParams { seed: 1234 },
+            octaves: Nibble::new(octaves),
+            frequency: UNFloat::new(frequency),
+            lacunarity: UNFloat::new(0.5),
+            persistence: UNFloat::new(0.5),
+        };
+
+        assert_eq!(params(0, 0.0).octave_count(), 1);
+        assert_eq!(params(15, 0.0).octave_count(), 8);
+        assert_eq!(params(0, 0.0).scaled_frequency(), 0.5);
+        assert_eq!(params(0, 1.0).scaled_frequency(), 4.0);
+
+        let sample = |p: FractalParams| <Fbm as NoiseFunction>::new(&p).get([0.37, -0.21, 0.05]);
+
+        assert_eq!(sample(params(4, 0.3)), sample(params(4, 0.3)));
+        assert_ne!(sample(params(0, 0.3)), sample(params(15, 0.3)));
+        assert_ne!(sample(params(4, 0.0)), sample(params(4, 1.0)));
+    }
+
+    #[test]
+    fn test_fractal_params_load_legacy_saves() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+
+        let params: FractalParams = serde_yaml::from_str("seed: 7").unwrap();
+        assert_eq!(params.seed.seed, 7);
+        assert_eq!(params.octave_count(), Fbm::DEFAULT_OCTAVE_COUNT);
+        assert!(close(params.scaled_frequency(), Fbm::DEFAULT_FREQUENCY));
+        assert!(close(params.scaled_lacunarity(), Fbm::DEFAULT_LACUNARITY));
+        assert!(close(params.scaled_persistence(), Fbm::DEFAULT_PERSISTENCE));
+
+        let point = [0.37, -0.21, 0.05];
+        assert!(close(
+            <Fbm as NoiseFunction>::new(&params).get(point),
+            Fbm::default().set_seed(7).get(point)
+        ));
+
+        let ridged: RidgedMultiParams =
+            serde_yaml::from_str("attenuation:\n  value: 0.25\nseed: 7").unwrap();
+        assert_eq!(ridged.attenuation, UNFloat::new(0.25));
+        assert_eq!(ridged.fractal.seed.seed, 7);
+        assert_eq!(
+            ridged.fractal.octave_count(),
+            FractalParams::default().octave_count()
+        );
+    }
+
+    #[test]
+    fn test_identity_curve() {
+        let base = || {
+            Box::new(NoiseFunctions::HashValue(Noise::generate_rng(
+                &mut DeterministicRng::seed_from_u64(0),
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            )))
+        };
+
+        let identity = NoiseFunctions::Curve(NoiseCurve::new(
+            base(),
+            vec![
+                (SNFloat::ONE, SNFloat::ONE),
+                (SNFloat::NEG_ONE, SNFloat::NEG_ONE),
+                (SNFloat::ZERO, SNFloat::ZERO),
+            ],
+        ));
+        let base = base();
+
+        for i in 0..100 {
+            let (x, y, t) = (i as f64 * 0.37, i as f64 * -0.21, i as f64 * 0.05);
+
+            assert!((identity.compute(x, y, t) - base.compute(x, y, t)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_curve_is_monotone_and_clamped() {
+        let curve = NoiseCurve::new(
+            Box::new(NoiseFunctions::HashValue(Noise::generate_rng(
+                &mut DeterministicRng::seed_from_u64(0),
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            ))),
+            vec![
+                (SNFloat::new(-0.5), SNFloat::new(-1.0)),
+                (SNFloat::new(0.0), SNFloat::new(0.9)),
+                (SNFloat::new(0.1), SNFloat::new(1.0)),
+                (SNFloat::new(0.5), SNFloat::new(1.0)),
+            ],
+        );
+
+        assert_eq!(curve.remap(-1.0), -1.0);
+        assert_eq!(curve.remap(0.8), 1.0);
+
+        let samples: Vec<f64> = (0..=100)
+            .map(|i| curve.remap(i as f64 / 100.0 - 0.5))
+            .collect();
+        assert!(samples.windows(2).all(|w| w[0] <= w[1] + 1e-12));
+        assert!(samples.iter().all(|&v| (-1.0..=1.0).contains(&v)));
+    }
+
+    #[test]
+    fn test_curve_deserializes_with_spline() {
+        let curve = NoiseCurve::new(
+            Box::new(NoiseFunctions::HashValue(Noise::generate_rng(
+                &mut DeterministicRng::seed_from_u64(0),
+                ProtoGenArg {
+                    profiler: &mut None,
+                },
+            ))),
+            vec![
+                (SNFloat::new(-0.5), SNFloat::new(-1.0)),
+                (SNFloat::new(0.2), SNFloat::new(0.3)),
+                (SNFloat::new(0.5), SNFloat::new(1.0)),
+            ],
+        );
+
+        let reloaded: NoiseCurve =
+            serde_yaml::from_str(&serde_yaml::to_string(&curve).unwrap()).unwrap();
+
+        for i in 0..=100 {
+            let value = i as f64 / 50.0 - 1.0;
+
+            assert_eq!(curve.remap(value), reloaded.remap(value));
+        }
+
+        let mut saved = serde_json::to_value(&curve).unwrap();
+        saved["control_points"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<NoiseCurve>(saved).is_err());
+    }
+}
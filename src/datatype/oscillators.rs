@@ -0,0 +1,112 @@
+//! Datatypes with time-varying state advance it in Updatable::update, once per tick,
+//! so an update_recursively pass over a tree animates everything in it.
+
+use std::f32::consts::PI;
+
+use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A low frequency oscillator. Each update advances phase by freq of a full turn,
+/// and value is the waveform normaliser's response to phase mapped into [-1, 1).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Oscillator {
+    pub freq: UNFloat,
+    pub phase: Angle,
+    pub waveform: SFloatNormaliser,
+    pub value: SNFloat,
+}
+
+impl Oscillator {
+    pub fn new(freq: UNFloat, phase: Angle, waveform: SFloatNormaliser) -> Self {
+        Self {
+            freq,
+            phase,
+            waveform,
+            value: Self::sample(phase, waveform),
+        }
+    }
+
+    fn sample(phase: Angle, waveform: SFloatNormaliser) -> SNFloat {
+        waveform.normalise(phase.into_inner() / PI)
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(
+            UNFloat::random(rng),
+            Angle::random(rng),
+            SFloatNormaliser::generate_rng(rng, ()),
+        )
+    }
+}
+
+impl<'a> Generatable<'a> for Oscillator {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for Oscillator {
+    type MutArg = ProtoMutArg<'a>;
+
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
+        match rng.gen_range(0..3) {
+            0 => self.freq = UNFloat::random(rng),
+            1 => self.phase = Angle::random(rng),
+            2 => self.waveform = SFloatNormaliser::generate_rng(rng, ()),
+            _ => unreachable!(),
+        }
+
+        self.value = Self::sample(self.phase, self.waveform);
+    }
+}
+
+impl<'a> Updatable<'a> for Oscillator {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {
+        self.phase =
+            Angle::new_wrapped(self.phase.into_inner() + self.freq.into_inner() * 2.0 * PI);
+        self.value = Self::sample(self.phase, self.waveform);
+    }
+}
+
+impl<'a> UpdatableRecursively<'a> for Oscillator {
+    fn update_recursively(&mut self, arg: ProtoUpdArg<'a>) {
+        self.update(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_oscillator_traces_waveform() {
+        let freq = UNFloat::new(1.0 / 16.0);
+        let mut sine = Oscillator::new(freq, Angle::ZERO, SFloatNormaliser::SinRepeating);
+        let mut saw = Oscillator::new(freq, Angle::ZERO, SFloatNormaliser::Sawtooth);
+
+        for i in 1..=32 {
+            sine.update(ProtoUpdArg {
+                profiler: &mut None,
+            });
+            saw.update(ProtoUpdArg {
+                profiler: &mut None,
+            });
+
+            let phase = Angle::new_wrapped(i as f32 * 2.0 * PI / 16.0).into_inner();
+
+            assert_relative_eq!(sine.value.into_inner(), phase.sin(), epsilon = 1e-4);
+            assert_relative_eq!(saw.value.into_inner(), phase / PI, epsilon = 1e-4);
+        }
+
+        assert_relative_eq!(sine.phase.into_inner(), 0.0, epsilon = 1e-4);
+    }
+}
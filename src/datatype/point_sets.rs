@@ -4,27 +4,194 @@ use std::{
     sync::Arc,
 };
 
+use failure::{ensure, Fallible};
 use float_ord::FloatOrd;
 use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
 use nalgebra::*;
 use ndarray::Array2;
+use num::integer::gcd;
 use rand::prelude::*;
-use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
+use serde::{
+    de::{self, Deserializer},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
 
 use crate::prelude::*;
 
+/// Points closer than this on both axes are treated as the same point
+pub const POINT_EPSILON: f32 = 1e-6;
+
+/// The most points a set may hold while every point stays addressable through Index<Byte>.
+/// Generated sets are capped here; larger sets have to be built explicitly with new_large.
+pub const MAX_BYTE_INDEXED_POINTS: usize = 256;
+
+/// The most points an Explicit set will load from a save, so a corrupt file can't demand unbounded memory
+pub const MAX_EXPLICIT_POINTS: usize = 65536;
+
 #[derive(Clone, Debug)]
 pub struct PointSet {
     points: Arc<Vec<SNPoint>>,
     generator: PointSetGenerator,
+    seed: u64,
 }
 
 impl PointSet {
     #[track_caller]
     pub fn new(points: Arc<Vec<SNPoint>>, generator: PointSetGenerator) -> Self {
-        assert!(points.len() > 0);
-        assert!(points.len() <= 256);
-        Self { points, generator }
+        Self::new_large(points, generator, MAX_BYTE_INDEXED_POINTS)
+    }
+
+    /// Like new, but allows up to max points. Points past the 256th can't be reached through Index<Byte>.
+    #[track_caller]
+    pub fn new_large(points: Arc<Vec<SNPoint>>, generator: PointSetGenerator, max: usize) -> Self {
+        Self::try_new_large(points, generator, max).unwrap()
+    }
+
+    /// Like new_large, but fails instead of panicking on an empty or oversized set
+    pub fn try_new_large(
+        points: Arc<Vec<SNPoint>>,
+        generator: PointSetGenerator,
+        max: usize,
+    ) -> Fallible<Self> {
+        ensure!(
+            points.len() <= max,
+            "PointSet has {} points, more than the maximum of {}",
+            points.len(),
+            max
+        );
+
+        let point_set = Self {
+            points,
+            generator,
+            seed: 0,
+        };
+
+        point_set.validate()?;
+        Ok(point_set)
+    }
+
+    /// Checks the invariants every constructor upholds
+    pub fn validate(&self) -> Fallible<()> {
+        ensure!(
+            !self.points.is_empty(),
+            "PointSet must contain at least one point"
+        );
+
+        Ok(())
+    }
+
+    /// The seed the points were generated from, saved alongside the generator so that
+    /// stochastic generators reproduce the same points when deserialized
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// True if every point can be addressed with a Byte index
+    pub fn is_byte_indexable(&self) -> bool {
+        self.points.len() <= MAX_BYTE_INDEXED_POINTS
+    }
+
+    /// Offsets every point by up to amount on each axis, keeping the count and generator.
+    /// Jittered positions are not serialized, as the points are regenerated on load,
+    /// except for Explicit sets whose stored points are jittered too.
+    pub fn jitter<R: Rng + ?Sized>(&mut self, rng: &mut R, amount: UNFloat) {
+        let amount = amount.into_inner();
+
+        if amount <= 0.0 {
+            return;
+        }
+
+        let mut offset =
+            |v: SNFloat| SNFloat::new_clamped(v.into_inner() + rng.gen_range(-amount..=amount));
+
+        let points: Vec<SNPoint> = self
+            .points
+            .iter()
+            .map(|p| SNPoint::from_snfloats(offset(p.x()), offset(p.y())))
+            .collect();
+
+        if let PointSetGenerator::Explicit { points: explicit } = &mut self.generator {
+            explicit.clone_from(&points);
+        }
+
+        self.points = Arc::new(points);
+    }
+
+    /// Transforms every point with SNPoint::apply_matrix, keeping the generator. Like jitter,
+    /// the transformed positions are not serialized.
+    pub fn apply_matrix(&self, m: SNFloatMatrix3, normaliser: SFloatNormaliser) -> PointSet {
+        PointSet {
+            points: Arc::new(
+                self.points
+                    .iter()
+                    .map(|p| p.apply_matrix(m, normaliser))
+                    .collect(),
+            ),
+            generator: self.generator.clone(),
+            seed: self.seed,
+        }
+    }
+
+    /// Unions copies of the set rotated about the origin by each multiple of 2π / folds.
+    /// Source points are evenly subsampled to stay within 256 points, and rotated points
+    /// that leave the unit square are clamped back into it.
+    pub fn symmetrize(&self, folds: Nibble) -> PointSet {
+        let folds = usize::from(folds.into_inner().max(1));
+        let keep = (MAX_BYTE_INDEXED_POINTS / folds)
+            .min(self.points.len())
+            .max(1);
+
+        let sources = (0..keep).map(|i| self.points[i * self.points.len() / keep]);
+
+        let points = sources
+            .flat_map(|p| {
+                (0..folds).map(move |fold| {
+                    let rotation = Rotation2::new(2.0 * PI * fold as f32 / folds as f32);
+
+                    SNPoint::new_normalised(rotation * p.into_inner(), SFloatNormaliser::Clamp)
+                })
+            })
+            .collect();
+
+        PointSet::from_points(points).unwrap()
+    }
+
+    /// Drops every point within epsilon on both axes of an earlier point that was kept
+    pub fn dedup(&self, epsilon: f32) -> PointSet {
+        let hash = SpatialHash::build(&self.points, epsilon.max(POINT_EPSILON));
+        let mut kept = vec![false; self.points.len()];
+
+        for (i, &p) in self.points.iter().enumerate() {
+            kept[i] = hash
+                .within(p, epsilon, &self.points, DistanceFunction::Chebyshev)
+                .into_iter()
+                .take_while(|&j| j < i)
+                .all(|j| !kept[j]);
+        }
+
+        let points = self
+            .points
+            .iter()
+            .zip(kept)
+            .filter_map(|(&p, kept)| kept.then_some(p))
+            .collect();
+
+        PointSet::from_points_large(points, self.points.len()).unwrap()
+    }
+
+    /// Builds a point set from arbitrary points, tagged with an Explicit generator so it serializes losslessly
+    pub fn from_points(points: Vec<SNPoint>) -> Fallible<Self> {
+        Self::from_points_large(points, MAX_BYTE_INDEXED_POINTS)
+    }
+
+    /// Like from_points, but allows up to max points
+    pub fn from_points_large(points: Vec<SNPoint>, max: usize) -> Fallible<Self> {
+        Self::try_new_large(
+            Arc::new(points.clone()),
+            PointSetGenerator::Explicit { points },
+            max,
+        )
     }
 
     pub fn get_offsets(&self, width: usize, height: usize) -> Vec<SNPoint> {
@@ -48,31 +215,163 @@ impl PointSet {
     }
 
     pub fn replace(&mut self, new_points: Arc<Vec<SNPoint>>) {
-        *self = Self::new(new_points, self.generator)
+        *self = Self {
+            seed: self.seed,
+            ..Self::new(new_points, self.generator.clone())
+        }
     }
 
     pub fn get_closest_point(&self, other: SNPoint) -> SNPoint {
         *self
             .points
             .iter()
-            .filter(|p| p.into_inner() != other.into_inner())
+            .filter(|p| !p.approx_eq(other, POINT_EPSILON))
             .min_by_key(|p| FloatOrd(distance(&p.into_inner(), &other.into_inner())))
             .unwrap_or(&other)
     }
 
+    /// The point of the set nearest to p under f, which is p itself if it's in the set
+    pub fn snap_point(&self, p: SNPoint, f: DistanceFunction) -> SNPoint {
+        self.points[self.nearest_index(p, f)]
+    }
+
     pub fn get_furthest_point(&self, other: SNPoint) -> SNPoint {
         *self
             .points
             .iter()
-            .filter(|p| p.into_inner() != other.into_inner())
+            .filter(|p| !p.approx_eq(other, POINT_EPSILON))
             .max_by_key(|p| FloatOrd(distance(&p.into_inner(), &other.into_inner())))
             .unwrap_or(&other)
     }
 
+    /// Index of the closest point under the given distance function, i.e. the id of the Voronoi cell containing other
+    pub fn nearest_index(&self, other: SNPoint, distance_function: DistanceFunction) -> usize {
+        self.nearest_index_with(&self.spatial_hash(), other, distance_function)
+    }
+
+    /// As nearest_index, reusing a hash from spatial_hash across many lookups
+    pub fn nearest_index_with(
+        &self,
+        hash: &SpatialHash,
+        other: SNPoint,
+        distance_function: DistanceFunction,
+    ) -> usize {
+        hash.nearest(other, &self.points, distance_function)
+    }
+
+    /// Buckets the points into cells holding about one point each
+    pub fn spatial_hash(&self) -> SpatialHash {
+        SpatialHash::build(&self.points, 2.0 / (self.points.len().max(1) as f32).sqrt())
+    }
+
+    /// Fills a width x height buffer with the normalised distance from each cell center to the nearest point
+    pub fn to_sdf(
+        &self,
+        width: usize,
+        height: usize,
+        distance_function: DistanceFunction,
+        normaliser: UFloatNormaliser,
+    ) -> Buffer<UNFloat> {
+        let mut buffer = Buffer::new(Array2::from_elem((height, width), UNFloat::ZERO));
+        let hash = self.spatial_hash();
+
+        for (center, value) in buffer.iter_points_mut() {
+            let nearest = self.points[self.nearest_index_with(&hash, center, distance_function)];
+
+            *value = distance_function.calculate_normalised(nearest, center, &normaliser);
+        }
+
+        buffer
+    }
+
+    /// One colour per point, from sampling the noise at the point and mapping it through the ramp
+    pub fn color_by_noise(
+        &self,
+        noise: &NoiseFunctions,
+        ramp: &ColorRamp,
+        t: f64,
+    ) -> Vec<FloatColor> {
+        self.points
+            .iter()
+            .map(|p| {
+                let value = noise.compute(p.x().into_inner() as f64, p.y().into_inner() as f64, t);
+
+                ramp.sample(UNFloat::new_clamped((value * 0.5 + 0.5) as f32))
+            })
+            .collect()
+    }
+
+    /// Edges from each point to its k nearest neighbours, as deduplicated index pairs with i < j
+    pub fn nearest_neighbour_graph(&self, k: Nibble) -> Vec<(usize, usize)> {
+        let k = usize::from(k.into_inner());
+
+        let mut edges: Vec<(usize, usize)> = (0..self.points.len())
+            .flat_map(|i| {
+                let p = self.points[i].into_inner();
+
+                let mut others: Vec<usize> = (0..self.points.len()).filter(|&j| j != i).collect();
+                others.sort_by_key(|&j| FloatOrd(distance(&p, &self.points[j].into_inner())));
+
+                others
+                    .into_iter()
+                    .take(k)
+                    .map(move |j| (i.min(j), i.max(j)))
+            })
+            .collect();
+
+        edges.sort_unstable();
+        edges.dedup();
+
+        edges
+    }
+
+    /// Edges of the relative neighbourhood graph: a and b are connected unless some c is closer to both of them than they are to each other
+    pub fn relative_neighbourhood_graph(&self) -> Vec<(usize, usize)> {
+        let n = self.points.len();
+        let d = |a: usize, b: usize| {
+            distance(&self.points[a].into_inner(), &self.points[b].into_inner())
+        };
+
+        (0..n)
+            .flat_map(|a| ((a + 1)..n).map(move |b| (a, b)))
+            .filter(|&(a, b)| {
+                let ab = d(a, b);
+
+                !(0..n).any(|c| c != a && c != b && d(a, c).max(d(b, c)) < ab)
+            })
+            .collect()
+    }
+
+    /// Center and radius of the smallest circle containing every point, by Welzl's algorithm
+    pub fn enclosing_circle(&self) -> (SNPoint, f32) {
+        let points: Vec<Point2<f32>> = self.points.iter().map(|p| p.into_inner()).collect();
+
+        let (center, radius) = match points.as_slice() {
+            [a] => (*a, 0.0),
+            [a, b] => circle_from_two(*a, *b),
+            _ => welzl(&points),
+        };
+
+        (
+            SNPoint::from_snfloats(
+                SNFloat::new_clamped(center.x),
+                SNFloat::new_clamped(center.y),
+            ),
+            radius,
+        )
+    }
+
+    /// Sorts the points nearest first, with any at zero distance last, and returns the first n.
+    /// Points at equal distance are ordered by x then y so the result doesn't depend on input order.
     pub fn get_n_closest_points(&mut self, other: SNPoint, n: usize) -> &[SNPoint] {
         Arc::make_mut(&mut self.points).sort_by_key(|p| {
             let d = distance(&p.into_inner(), &other.into_inner());
-            (d != 0.0, FloatOrd(d))
+            (
+                d != 0.0,
+                FloatOrd(d),
+                FloatOrd(p.x().into_inner()),
+                FloatOrd(p.y().into_inner()),
+            )
         });
 
         &self.points[0..n.min(self.points.len())]
@@ -101,6 +400,19 @@ impl Index<usize> for PointSet {
     }
 }
 
+/// Sets are equal when their points match in order, within POINT_EPSILON, whatever their generators
+impl PartialEq for PointSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.points.len() == other.points.len()
+            && self
+                .points
+                .iter()
+                .zip(other.points.iter())
+                .all(|(a, b)| a.approx_eq(*b, POINT_EPSILON))
+    }
+}
+
+/// Only reaches the first 256 points, see MAX_BYTE_INDEXED_POINTS
 impl Index<Byte> for PointSet {
     type Output = SNPoint;
     fn index(&self, idx: Byte) -> &Self::Output {
@@ -108,12 +420,43 @@ impl Index<Byte> for PointSet {
     }
 }
 
+/// How point sets are saved: the generator and the seed its points were drawn with
+#[derive(Serialize, Deserialize)]
+struct SeededGenerator {
+    generator: PointSetGenerator,
+    seed: u64,
+}
+
+/// Sets saved before seeds were recorded hold only the generator, and get a fresh seed on load
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedGenerator {
+    Seeded(SeededGenerator),
+    Unseeded(PointSetGenerator),
+}
+
+impl SavedGenerator {
+    fn into_seeded(self) -> SeededGenerator {
+        match self {
+            SavedGenerator::Seeded(seeded) => seeded,
+            SavedGenerator::Unseeded(generator) => SeededGenerator {
+                generator,
+                seed: thread_rng().gen(),
+            },
+        }
+    }
+}
+
 impl Serialize for PointSet {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        self.generator.serialize(serializer)
+        SeededGenerator {
+            generator: self.generator.clone(),
+            seed: self.seed,
+        }
+        .serialize(serializer)
     }
 }
 
@@ -122,7 +465,19 @@ impl<'de> Deserialize<'de> for PointSet {
     where
         D: Deserializer<'de>,
     {
-        Ok(PointSetGenerator::deserialize(deserializer)?.load())
+        let SeededGenerator { generator, seed } =
+            SavedGenerator::deserialize(deserializer)?.into_seeded();
+
+        match generator {
+            // May have been built through from_points_large, so only the explicit cap applies.
+            // The seed doesn't affect the points but is kept so the set reserializes the same.
+            PointSetGenerator::Explicit { points } => {
+                PointSet::from_points_large(points, MAX_EXPLICIT_POINTS)
+                    .map(|point_set| PointSet { seed, ..point_set })
+                    .map_err(de::Error::custom)
+            }
+            generator => Ok(generator.generate_seeded(seed)),
+        }
     }
 }
 
@@ -136,8 +491,21 @@ impl<'a> Generatable<'a> for PointSet {
 
 impl<'a> Mutatable<'a> for PointSet {
     type MutArg = ProtoMutArg<'a>;
-    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
-        *self = Self::random(rng);
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        // Occasionally drift the existing points, otherwise evolve the generator and regenerate
+        if rng.gen_bool(0.2) {
+            let amount = UNFloat::new(0.1 * arg.magnitude().into_inner());
+            self.jitter(rng, amount);
+
+            // The drifted points can't be regenerated from the seed, so save them as they are
+            self.generator = PointSetGenerator::Explicit {
+                points: self.points.to_vec(),
+            };
+        } else {
+            let mut generator = self.generator.clone();
+            generator.mutate_rng(rng, arg);
+            *self = generator.generate_point_set(rng);
+        }
     }
 }
 
@@ -151,11 +519,182 @@ impl<'a> UpdatableRecursively<'a> for PointSet {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
+pub struct WeightedPointSet {
+    points: Arc<Vec<SNPoint>>,
+    weights: Arc<Vec<UNFloat>>,
+    generator: PointSetGenerator,
+    seed: u64,
+}
+
+impl WeightedPointSet {
+    #[track_caller]
+    pub fn new(
+        points: Arc<Vec<SNPoint>>,
+        weights: Arc<Vec<UNFloat>>,
+        generator: PointSetGenerator,
+    ) -> Self {
+        Self::try_new(points, weights, generator).unwrap()
+    }
+
+    /// Like new, but fails instead of panicking on an empty or oversized set or mismatched weights
+    pub fn try_new(
+        points: Arc<Vec<SNPoint>>,
+        weights: Arc<Vec<UNFloat>>,
+        generator: PointSetGenerator,
+    ) -> Fallible<Self> {
+        let point_set = Self {
+            points,
+            weights,
+            generator,
+            seed: 0,
+        };
+
+        point_set.validate()?;
+        Ok(point_set)
+    }
+
+    /// Checks the invariants every constructor upholds
+    pub fn validate(&self) -> Fallible<()> {
+        ensure!(
+            !self.points.is_empty() && self.points.len() <= MAX_BYTE_INDEXED_POINTS,
+            "WeightedPointSet must contain between 1 and {} points, got {}",
+            MAX_BYTE_INDEXED_POINTS,
+            self.points.len()
+        );
+        ensure!(
+            self.points.len() == self.weights.len(),
+            "WeightedPointSet has {} points but {} weights",
+            self.points.len(),
+            self.weights.len()
+        );
+
+        Ok(())
+    }
+
+    /// The seed the points were generated from, see PointSet::seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn points(&self) -> &[SNPoint] {
+        &self.points
+    }
+
+    pub fn weights(&self) -> &[UNFloat] {
+        &self.weights
+    }
+
+    pub fn get(&self, idx: usize) -> (SNPoint, UNFloat) {
+        (self.points[idx], self.weights[idx])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SNPoint, UNFloat)> + '_ {
+        self.points
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn generator(&self) -> &PointSetGenerator {
+        &self.generator
+    }
+
+    pub fn to_point_set(&self) -> PointSet {
+        PointSet {
+            seed: self.seed,
+            ..PointSet::new(Arc::clone(&self.points), self.generator.clone())
+        }
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        PointSetGenerator::PackedCircles {
+            count: Byte::random(rng),
+            min_radius: UNFloat::random(rng),
+            max_radius: UNFloat::random(rng),
+        }
+        .generate_weighted_point_set(rng)
+    }
+}
+
+impl Default for WeightedPointSet {
+    fn default() -> Self {
+        WeightedPointSet::new(
+            Arc::new(origin()),
+            Arc::new(vec![UNFloat::ONE]),
+            PointSetGenerator::Origin,
+        )
+    }
+}
+
+impl Serialize for WeightedPointSet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SeededGenerator {
+            generator: self.generator.clone(),
+            seed: self.seed,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WeightedPointSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let SeededGenerator { generator, seed } =
+            SavedGenerator::deserialize(deserializer)?.into_seeded();
+
+        Ok(generator.generate_weighted_seeded(seed))
+    }
+}
+
+impl<'a> Generatable<'a> for WeightedPointSet {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for WeightedPointSet {
+    type MutArg = ProtoMutArg<'a>;
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
+        *self = Self::random(rng);
+    }
+}
+
+impl<'a> Updatable<'a> for WeightedPointSet {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for WeightedPointSet {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum PointSetGenerator {
     // Reasonable default - The Empty set is liable to crash some algorithms
     Origin,
 
+    // Arbitrary points, stored as-is so they serialize back to the same set
+    Explicit {
+        points: Vec<SNPoint>,
+    },
+
     Moore,
     VonNeumann,
     UniformGrid {
@@ -183,6 +722,28 @@ pub enum PointSetGenerator {
         count: Byte,
         radius: UNFloat,
     },
+    /// Poisson points that wrap around the edges, for seamless tiling
+    PoissonTiling {
+        count: Byte,
+        radius: UNFloat,
+    },
+    Rose {
+        count: Byte,
+        numerator: Nibble,
+        denominator: Nibble,
+    },
+    Superformula {
+        count: Byte,
+        m: Nibble,
+        n1: UNFloat,
+        n2: UNFloat,
+        n3: UNFloat,
+    },
+    /// The feature points Worley noise measures distance to, one jittered point per grid cell
+    WorleyFeatures {
+        count: Byte,
+        seed: SeedParams,
+    },
     Spiral {
         count: Byte,
         scalar: UNFloat,
@@ -200,15 +761,24 @@ pub enum PointSetGenerator {
     FibonacciRings {
         max_count: Byte, //full count will be less than this
     },
-    //TODO add fibonacci spiral also
+    /// Sunflower layout, each point a golden angle further round and evenly spread by area
+    FibonacciSpiral {
+        count: Byte,
+        scalar: UNFloat,
+    },
     SquaredRings {
         max_count: Byte, //full count will be less than this
     },
+    PackedCircles {
+        count: Byte,
+        min_radius: UNFloat,
+        max_radius: UNFloat,
+    },
 }
 
 impl PointSetGenerator {
     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
-        match rng.gen_range(0..13) {
+        match rng.gen_range(0..19) {
             // Skip Origin
             0 => PointSetGenerator::Moore,
             1 => PointSetGenerator::VonNeumann,
@@ -257,13 +827,51 @@ impl PointSetGenerator {
             12 => PointSetGenerator::SquaredRings {
                 max_count: Byte::random(rng),
             },
+            13 => PointSetGenerator::PackedCircles {
+                count: Byte::random(rng),
+                min_radius: UNFloat::random(rng),
+                max_radius: UNFloat::random(rng),
+            },
+            14 => PointSetGenerator::PoissonTiling {
+                count: Byte::random(rng),
+                radius: UNFloat::random(rng),
+            },
+            15 => PointSetGenerator::Rose {
+                count: Byte::random(rng),
+                numerator: Nibble::random(rng),
+                denominator: Nibble::random(rng),
+            },
+            16 => PointSetGenerator::Superformula {
+                count: Byte::random(rng),
+                m: Nibble::random(rng),
+                n1: UNFloat::random(rng),
+                n2: UNFloat::random(rng),
+                n3: UNFloat::random(rng),
+            },
+            17 => PointSetGenerator::WorleyFeatures {
+                count: Byte::random(rng),
+                seed: SeedParams::random(rng),
+            },
+            18 => PointSetGenerator::FibonacciSpiral {
+                count: Byte::random(rng),
+                scalar: UNFloat::random(rng),
+            },
             _ => unreachable!(),
         }
     }
 
     pub fn generate_point_set<R: Rng + ?Sized>(&self, rng: &mut R) -> PointSet {
+        self.generate_seeded(rng.gen())
+    }
+
+    /// Generates the points from a DeterministicRng seeded with seed, so the same generator and
+    /// seed always give the same points
+    pub fn generate_seeded(&self, seed: u64) -> PointSet {
+        let rng = &mut DeterministicRng::seed_from_u64(seed);
+
         let points = match self {
             PointSetGenerator::Origin => origin(),
+            PointSetGenerator::Explicit { points } => points.clone(),
             PointSetGenerator::Moore => moore(),
             PointSetGenerator::VonNeumann => von_neumann(),
             PointSetGenerator::UniformGrid { x_count, y_count } => {
@@ -307,13 +915,16 @@ impl PointSetGenerator {
                 let x_mod = if x_mod.into_inner() { 1 } else { 0 };
                 let y_mod = if y_mod.into_inner() { 1 } else { 0 };
 
+                // A lone cell is never masked out, so the grid is never empty
+                let single = x_count == 1 && y_count == 1;
+
                 let x_ratio = 1.0 / x_count as f32;
                 let y_ratio = 1.0 / y_count as f32;
 
                 (0..x_count)
                     .flat_map(|x| {
                         (0..y_count)
-                            .filter(move |y| !(x % 2 == x_mod && y % 2 == y_mod))
+                            .filter(move |y| single || !(x % 2 == x_mod && y % 2 == y_mod))
                             .map(move |y| {
                                 SNPoint::new(Point2::new(
                                     2.0 * (x_ratio * x as f32 + x_ratio * 0.5) - 1.0,
@@ -396,8 +1007,16 @@ impl PointSetGenerator {
                     (2.0 * radius.into_inner() / (count.into_inner() as f32).sqrt().max(2.0))
                         .max(0.01),
                     normaliser,
+                    false,
                 )
             }
+            PointSetGenerator::PoissonTiling { count, radius } => poisson(
+                rng,
+                count.into_inner().max(4) as usize,
+                (2.0 * radius.into_inner() / (count.into_inner() as f32).sqrt().max(2.0)).max(0.01),
+                SFloatNormaliser::Sawtooth,
+                true,
+            ),
             PointSetGenerator::Spiral {
                 count,
                 scalar,
@@ -588,6 +1207,47 @@ impl PointSetGenerator {
                     })
                     .collect()
             }
+            PointSetGenerator::PackedCircles {
+                count,
+                min_radius,
+                max_radius,
+            } => {
+                packed_circles(
+                    rng,
+                    count.into_inner().max(1) as usize,
+                    *min_radius,
+                    *max_radius,
+                )
+                .0
+            }
+            PointSetGenerator::Rose {
+                count,
+                numerator,
+                denominator,
+            } => rose(
+                count.into_inner().max(1) as usize,
+                numerator.into_inner(),
+                denominator.into_inner().max(1),
+            ),
+            PointSetGenerator::Superformula {
+                count,
+                m,
+                n1,
+                n2,
+                n3,
+            } => superformula(
+                count.into_inner().max(1) as usize,
+                f32::from(m.into_inner()),
+                0.1 + n1.into_inner() * 9.9,
+                n2.into_inner() * 10.0,
+                n3.into_inner() * 10.0,
+            ),
+            PointSetGenerator::WorleyFeatures { count, seed } => {
+                worley_features(count.into_inner().max(1) as usize, seed.seed)
+            }
+            PointSetGenerator::FibonacciSpiral { count, scalar } => {
+                fibonacci_spiral(count.into_inner().max(1) as usize, *scalar)
+            }
         };
 
         assert!(
@@ -596,23 +1256,189 @@ impl PointSetGenerator {
             self
         );
 
-        PointSet::new(Arc::new(points), *self)
+        let max = match self {
+            PointSetGenerator::Explicit { points } => points.len(),
+            _ => MAX_BYTE_INDEXED_POINTS,
+        };
+
+        PointSet {
+            seed,
+            ..PointSet::new_large(Arc::new(points), self.clone(), max)
+        }
     }
 
-    fn load(&self) -> PointSet {
-        self.generate_point_set(&mut rand::thread_rng())
+    /// Generates a point set along with a weight for each point.
+    /// PackedCircles weights are the circle radii, every other generator weights its points at one.
+    pub fn generate_weighted_point_set<R: Rng + ?Sized>(&self, rng: &mut R) -> WeightedPointSet {
+        self.generate_weighted_seeded(rng.gen())
     }
-}
 
-impl Default for PointSetGenerator {
-    fn default() -> Self {
-        PointSetGenerator::Origin
+    /// Like generate_seeded, for weighted point sets
+    pub fn generate_weighted_seeded(&self, seed: u64) -> WeightedPointSet {
+        let weighted = match self {
+            PointSetGenerator::PackedCircles {
+                count,
+                min_radius,
+                max_radius,
+            } => {
+                let (points, radii) = packed_circles(
+                    &mut DeterministicRng::seed_from_u64(seed),
+                    count.into_inner().max(1) as usize,
+                    *min_radius,
+                    *max_radius,
+                );
+
+                WeightedPointSet::new(Arc::new(points), Arc::new(radii), self.clone())
+            }
+            _ => {
+                let point_set = self.generate_seeded(seed);
+                let weights = vec![UNFloat::ONE; point_set.len()];
+
+                WeightedPointSet::new(point_set.points, Arc::new(weights), self.clone())
+            }
+        };
+
+        WeightedPointSet { seed, ..weighted }
     }
 }
 
-fn origin() -> Vec<SNPoint> {
-    vec![SNPoint::zero()]
-}
+impl<'a> Mutatable<'a> for PointSetGenerator {
+    type MutArg = ProtoMutArg<'a>;
+
+    /// Usually nudges one parameter of the current variant, occasionally switching to a fresh
+    /// random variant. Variants without parameters always switch.
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen_bool(0.1) {
+            *self = Self::random(rng);
+            return;
+        }
+
+        match self {
+            PointSetGenerator::Origin
+            | PointSetGenerator::Moore
+            | PointSetGenerator::VonNeumann => *self = Self::random(rng),
+            PointSetGenerator::Explicit { points } => {
+                let index = rng.gen_range(0..points.len());
+                points[index].mutate_rng(rng, arg);
+            }
+            PointSetGenerator::UniformGrid { x_count, y_count }
+            | PointSetGenerator::HexGrid { x_count, y_count }
+            | PointSetGenerator::TriGrid { x_count, y_count } => {
+                if rng.gen() {
+                    x_count.mutate_rng(rng, arg)
+                } else {
+                    y_count.mutate_rng(rng, arg)
+                }
+            }
+            PointSetGenerator::SparseGrid {
+                x_count,
+                y_count,
+                x_mod,
+                y_mod,
+            } => match rng.gen_range(0..4) {
+                0 => x_count.mutate_rng(rng, arg),
+                1 => y_count.mutate_rng(rng, arg),
+                2 => x_mod.mutate_rng(rng, arg),
+                3 => y_mod.mutate_rng(rng, arg),
+                _ => unreachable!(),
+            },
+            PointSetGenerator::UniformDistribution { count } => count.mutate_rng(rng, arg),
+            PointSetGenerator::Poisson { count, radius }
+            | PointSetGenerator::PoissonTiling { count, radius } => {
+                if rng.gen() {
+                    count.mutate_rng(rng, arg)
+                } else {
+                    radius.mutate_rng(rng, arg)
+                }
+            }
+            PointSetGenerator::Rose {
+                count,
+                numerator,
+                denominator,
+            } => match rng.gen_range(0..3) {
+                0 => count.mutate_rng(rng, arg),
+                1 => numerator.mutate_rng(rng, arg),
+                2 => denominator.mutate_rng(rng, arg),
+                _ => unreachable!(),
+            },
+            PointSetGenerator::Superformula {
+                count,
+                m,
+                n1,
+                n2,
+                n3,
+            } => match rng.gen_range(0..5) {
+                0 => count.mutate_rng(rng, arg),
+                1 => m.mutate_rng(rng, arg),
+                2 => n1.mutate_rng(rng, arg),
+                3 => n2.mutate_rng(rng, arg),
+                4 => n3.mutate_rng(rng, arg),
+                _ => unreachable!(),
+            },
+            PointSetGenerator::WorleyFeatures { count, seed } => {
+                if rng.gen() {
+                    count.mutate_rng(rng, arg)
+                } else {
+                    seed.mutate_rng(rng, arg)
+                }
+            }
+            PointSetGenerator::FibonacciSpiral { count, scalar } => {
+                if rng.gen() {
+                    count.mutate_rng(rng, arg)
+                } else {
+                    scalar.mutate_rng(rng, arg)
+                }
+            }
+            PointSetGenerator::Spiral {
+                count,
+                scalar,
+                maximum,
+                linear,
+                nonlinearity_factor_halved,
+            } => match rng.gen_range(0..5) {
+                0 => count.mutate_rng(rng, arg),
+                1 => scalar.mutate_rng(rng, arg),
+                2 => maximum.mutate_rng(rng, arg),
+                3 => linear.mutate_rng(rng, arg),
+                4 => nonlinearity_factor_halved.mutate_rng(rng, arg),
+                _ => unreachable!(),
+            },
+            PointSetGenerator::RandomRings { max_rings } => max_rings.mutate_rng(rng, arg),
+            PointSetGenerator::LinearIncreasingRings {
+                max_count,
+                ring_size_delta,
+            } => {
+                if rng.gen() {
+                    max_count.mutate_rng(rng, arg)
+                } else {
+                    ring_size_delta.mutate_rng(rng, arg)
+                }
+            }
+            PointSetGenerator::FibonacciRings { max_count }
+            | PointSetGenerator::SquaredRings { max_count } => max_count.mutate_rng(rng, arg),
+            PointSetGenerator::PackedCircles {
+                count,
+                min_radius,
+                max_radius,
+            } => match rng.gen_range(0..3) {
+                0 => count.mutate_rng(rng, arg),
+                1 => min_radius.mutate_rng(rng, arg),
+                2 => max_radius.mutate_rng(rng, arg),
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+impl Default for PointSetGenerator {
+    fn default() -> Self {
+        PointSetGenerator::Origin
+    }
+}
+
+fn origin() -> Vec<SNPoint> {
+    vec![SNPoint::zero()]
+}
 
 fn moore() -> Vec<SNPoint> {
     vec![
@@ -636,23 +1462,194 @@ fn von_neumann() -> Vec<SNPoint> {
     ]
 }
 
+/// Iterative form of Welzl's algorithm, growing the circle whenever a point falls outside it
+fn welzl(points: &[Point2<f32>]) -> (Point2<f32>, f32) {
+    const EPSILON: f32 = 1e-5;
+
+    let outside = |(center, radius): (Point2<f32>, f32), p: Point2<f32>| {
+        distance(&center, &p) > radius + EPSILON
+    };
+
+    let mut circle = (points[0], 0.0);
+
+    for i in 1..points.len() {
+        if !outside(circle, points[i]) {
+            continue;
+        }
+
+        circle = (points[i], 0.0);
+
+        for j in 0..i {
+            if !outside(circle, points[j]) {
+                continue;
+            }
+
+            circle = circle_from_two(points[i], points[j]);
+
+            for k in 0..j {
+                if outside(circle, points[k]) {
+                    circle = circle_from_three(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+
+    circle
+}
+
+fn circle_from_two(a: Point2<f32>, b: Point2<f32>) -> (Point2<f32>, f32) {
+    (nalgebra::center(&a, &b), distance(&a, &b) * 0.5)
+}
+
+/// The circumcircle, or for collinear points the circle across the furthest pair
+fn circle_from_three(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> (Point2<f32>, f32) {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * (ab.x * ac.y - ab.y * ac.x);
+
+    if d.abs() < 1e-9 {
+        return [(a, b), (a, c), (b, c)]
+            .iter()
+            .map(|&(p, q)| circle_from_two(p, q))
+            .max_by_key(|&(_, r)| FloatOrd(r))
+            .unwrap();
+    }
+
+    let offset = Vector2::new(
+        ac.y * ab.norm_squared() - ab.y * ac.norm_squared(),
+        ab.x * ac.norm_squared() - ac.x * ab.norm_squared(),
+    ) / d;
+
+    (a + offset, offset.norm())
+}
+
+/// Samples the rose curve r = cos((n / d) * theta) over the period needed to close it
+pub fn rose(count: usize, numerator: u8, denominator: u8) -> Vec<SNPoint> {
+    assert!(denominator > 0);
+
+    let divisor = gcd(numerator, denominator);
+    let n = numerator / divisor;
+    let d = denominator / divisor;
+
+    let period = if n % 2 == 1 && d % 2 == 1 {
+        PI * d as f32
+    } else {
+        2.0 * PI * d as f32
+    };
+
+    let k = n as f32 / d as f32;
+
+    (0..count)
+        .map(|i| {
+            let theta = period * i as f32 / count as f32;
+            let r = f32::cos(k * theta);
+
+            SNPoint::from_snfloats(
+                SNFloat::new_clamped(r * f32::cos(theta)),
+                SNFloat::new_clamped(r * f32::sin(theta)),
+            )
+        })
+        .collect()
+}
+
+/// Samples Gielis's superformula with a = b = 1 over a full turn, scaled so the furthest point
+/// sits on the unit circle. Radii that blow up for degenerate exponents collapse to the origin.
+pub fn superformula(count: usize, m: f32, n1: f32, n2: f32, n3: f32) -> Vec<SNPoint> {
+    let radius = |theta: f32| {
+        let r = ((m * theta / 4.0).cos().abs().powf(n2) + (m * theta / 4.0).sin().abs().powf(n3))
+            .powf(-1.0 / n1);
+
+        if r.is_finite() {
+            r
+        } else {
+            0.0
+        }
+    };
+
+    let samples: Vec<(f32, f32)> = (0..count)
+        .map(|i| {
+            let theta = 2.0 * PI * i as f32 / count as f32;
+
+            (theta, radius(theta))
+        })
+        .collect();
+
+    let max_radius = samples.iter().map(|&(_, r)| r).fold(0.0, f32::max);
+    let scale = if max_radius > 0.0 {
+        1.0 / max_radius
+    } else {
+        0.0
+    };
+
+    samples
+        .into_iter()
+        .map(|(theta, r)| {
+            SNPoint::from_snfloats(
+                SNFloat::new_clamped(r * scale * theta.cos()),
+                SNFloat::new_clamped(r * scale * theta.sin()),
+            )
+        })
+        .collect()
+}
+
+/// Places point i at i golden angles round and a radius of sqrt(i / count), so points cover the
+/// disc evenly. Scalar sets the outer radius, which is kept above a tenth so points stay distinct.
+pub fn fibonacci_spiral(count: usize, scalar: UNFloat) -> Vec<SNPoint> {
+    let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+    let outer_radius = 0.1 + 0.9 * scalar.into_inner();
+
+    (0..count)
+        .map(|i| {
+            SNPolarPoint::new(
+                UNFloat::new_clamped(outer_radius * (i as f32 / count as f32).sqrt()),
+                Angle::new_wrapped(i as f32 * golden_angle),
+            )
+            .to_cartesian()
+        })
+        .collect()
+}
+
+/// Splits the domain into a near-square grid of at least count cells and places one point in each
+/// of the first count cells, offset within the cell by a hash of its coordinates and the seed.
+pub fn worley_features(count: usize, seed: u32) -> Vec<SNPoint> {
+    let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+    let rows = count.div_ceil(columns);
+    let y_seed = wang_hash(seed);
+
+    (0..count)
+        .map(|i| {
+            let (x, y) = (i % columns, i / columns);
+            let jitter_x = pixel_hash(x, y, seed).into_inner();
+            let jitter_y = pixel_hash(x, y, y_seed).into_inner();
+
+            SNPoint::from_snfloats(
+                SNFloat::new_clamped(2.0 * (x as f32 + jitter_x) / columns as f32 - 1.0),
+                SNFloat::new_clamped(2.0 * (y as f32 + jitter_y) / rows as f32 - 1.0),
+            )
+        })
+        .collect()
+}
+
 pub fn uniform<R: Rng + ?Sized>(rng: &mut R, count: usize) -> Vec<SNPoint> {
     (0..count)
         .map(|_| SNPoint::new(Point2::new(rng.gen(), rng.gen())))
         .collect()
 }
 
+/// Bridson's Poisson disc sampling. When toroidal, candidates and the minimum distance check wrap
+/// around the [-1, 1] domain so the result tiles seamlessly, and the normaliser is unused.
 pub fn poisson<R: Rng + ?Sized>(
     rng: &mut R,
     count: usize,
     radius: f32,
     normaliser: SFloatNormaliser,
+    toroidal: bool,
 ) -> Vec<SNPoint> {
     assert!(radius > 0.0);
     assert!(count > 0);
 
-    let cell_size = radius / SQRT_2;
-    let grid_size = (1.0 / cell_size).ceil() as usize * 2;
+    // Cells evenly divide the domain, so wrapping around the edges never meets a partial cell
+    let grid_size = (1.0 / (radius / SQRT_2)).ceil() as usize * 2;
+    let cell_size = 2.0 / grid_size as f32;
 
     let p_to_grid = |p: SNPoint| {
         [
@@ -661,6 +1658,18 @@ pub fn poisson<R: Rng + ?Sized>(
         ]
     };
 
+    let wrap = |v: f32| SNFloat::new_clamped((v + 1.0).rem_euclid(2.0) - 1.0);
+
+    let grid_index = |g: usize, offset: isize| {
+        let g = g as isize + offset;
+
+        if toroidal {
+            g.rem_euclid(grid_size as isize) as usize
+        } else {
+            g.clamp(0, grid_size as isize - 1) as usize
+        }
+    };
+
     let mut grid: Array2<Option<u16>> = Array2::from_elem((grid_size, grid_size), None);
     let mut points = Vec::with_capacity(count);
     let mut active = Vec::with_capacity(count);
@@ -672,6 +1681,8 @@ pub fn poisson<R: Rng + ?Sized>(
 
     // Arbitrary parameter for number of neighbouring points to attempt
     const K: usize = 30;
+    // Points within radius can lie up to two cells away, as cells are radius / sqrt(2) wide
+    const REACH: isize = 2;
 
     while points.len() < count && !active.is_empty() {
         let active_idx = rng.gen_range(0..active.len());
@@ -690,22 +1701,30 @@ pub fn poisson<R: Rng + ?Sized>(
             let dx = f32::cos(theta) * r;
             let dy = f32::sin(theta) * r;
 
-            let new_p = SNPoint::from_snfloats(
-                normaliser.normalise(p.x().into_inner() + dx),
-                normaliser.normalise(p.y().into_inner() + dy),
-            );
+            let new_p = if toroidal {
+                SNPoint::from_snfloats(wrap(p.x().into_inner() + dx), wrap(p.y().into_inner() + dy))
+            } else {
+                SNPoint::from_snfloats(
+                    normaliser.normalise_rng(p.x().into_inner() + dx, rng),
+                    normaliser.normalise_rng(p.y().into_inner() + dy, rng),
+                )
+            };
 
             let [gx, gy] = p_to_grid(new_p);
 
-            for tx in -1i16..=1 {
-                for ty in -1i16..=1 {
-                    if let Some(i) = grid[[
-                        ((gx as i16 + tx).max(0) as usize).min(grid_size - 1),
-                        ((gy as i16 + ty).max(0) as usize).min(grid_size - 1),
-                    ]] {
+            for tx in -REACH..=REACH {
+                for ty in -REACH..=REACH {
+                    if let Some(i) = grid[[grid_index(gx, tx), grid_index(gy, ty)]] {
                         // TODO Parametrize to arbitrary distance functions
-                        if distance(&points[i as usize].into_inner(), &new_p.into_inner()) <= radius
-                        {
+                        let existing = points[i as usize];
+
+                        let d = if toroidal {
+                            toroidal_distance(existing, new_p)
+                        } else {
+                            distance(&existing.into_inner(), &new_p.into_inner())
+                        };
+
+                        if d <= radius {
                             continue 'candidates;
                         }
                     }
@@ -726,3 +1745,823 @@ pub fn poisson<R: Rng + ?Sized>(
 
     points
 }
+
+/// Euclidean distance on the [-1, 1] square with opposite edges joined
+pub fn toroidal_distance(a: SNPoint, b: SNPoint) -> f32 {
+    let wrapped = |d: f32| {
+        let d = d.abs();
+        d.min(2.0 - d)
+    };
+
+    let dx = wrapped(a.x().into_inner() - b.x().into_inner());
+    let dy = wrapped(a.y().into_inner() - b.y().into_inner());
+
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Greedily packs non-overlapping circles, starting at max_radius and shrinking towards min_radius
+/// whenever no free position can be found. Always places at least one circle.
+pub fn packed_circles<R: Rng + ?Sized>(
+    rng: &mut R,
+    count: usize,
+    min_radius: UNFloat,
+    max_radius: UNFloat,
+) -> (Vec<SNPoint>, Vec<UNFloat>) {
+    assert!(count > 0);
+
+    // Arbitrary parameters for candidate positions per radius and how quickly the radius shrinks
+    const K: usize = 30;
+    const SHRINK_FACTOR: f32 = 0.9;
+
+    let min_radius = min_radius.into_inner().max(0.01);
+    let max_radius = max_radius.into_inner().max(min_radius);
+
+    let mut points: Vec<SNPoint> = Vec::with_capacity(count);
+    let mut radii: Vec<UNFloat> = Vec::with_capacity(count);
+
+    let mut radius = max_radius;
+
+    while points.len() < count {
+        let new_p = (0..K).map(|_| SNPoint::random(rng)).find(|new_p| {
+            points.iter().zip(radii.iter()).all(|(p, r)| {
+                distance(&p.into_inner(), &new_p.into_inner()) >= r.into_inner() + radius
+            })
+        });
+
+        if let Some(new_p) = new_p {
+            points.push(new_p);
+            radii.push(UNFloat::new(radius));
+        } else if radius > min_radius {
+            radius = (radius * SHRINK_FACTOR).max(min_radius);
+        } else {
+            break;
+        }
+    }
+
+    (points, radii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_circles_do_not_overlap() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let min_radius = UNFloat::random(&mut rng);
+            let max_radius = UNFloat::random(&mut rng);
+            let (points, radii) = packed_circles(&mut rng, 256, min_radius, max_radius);
+
+            assert!(!points.is_empty());
+            assert!(points.len() <= 256);
+            assert_eq!(points.len(), radii.len());
+
+            for i in 0..points.len() {
+                for j in (i + 1)..points.len() {
+                    let d = distance(&points[i].into_inner(), &points[j].into_inner());
+                    assert!(d >= radii[i].into_inner() + radii[j].into_inner() - 0.0001);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed_circles_radii_in_bounds() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let a = UNFloat::random(&mut rng).into_inner().max(0.01);
+            let b = UNFloat::random(&mut rng).into_inner().max(0.01);
+            let (min_radius, max_radius) = (a.min(b), a.max(b));
+
+            let (_, radii) = packed_circles(
+                &mut rng,
+                64,
+                UNFloat::new(min_radius),
+                UNFloat::new(max_radius),
+            );
+
+            for r in radii {
+                assert!(r.into_inner() >= min_radius && r.into_inner() <= max_radius);
+            }
+        }
+    }
+
+    #[test]
+    fn test_point_set_from_points() {
+        let points = vec![
+            SNPoint::zero(),
+            SNPoint::from_snfloats(SNFloat::ONE, SNFloat::ONE),
+            SNPoint::from_snfloats(SNFloat::NEG_ONE, SNFloat::ONE),
+            SNPoint::from_snfloats(SNFloat::new(0.25), SNFloat::new(-0.5)),
+            SNPoint::from_snfloats(SNFloat::new(-0.75), SNFloat::NEG_ONE),
+        ];
+
+        let a = PointSet::from_points(points.clone()).unwrap();
+        assert_eq!(a.points(), &points[..]);
+
+        let b: PointSet = serde_yaml::from_str(&serde_yaml::to_string(&a).unwrap()).unwrap();
+        assert_eq!(b.points(), &points[..]);
+
+        assert!(PointSet::from_points(Vec::new()).is_err());
+        assert!(PointSet::from_points(vec![SNPoint::zero(); 257]).is_err());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let point = |x: f32, y: f32| SNPoint::from_snfloats(SNFloat::new(x), SNFloat::new(y));
+
+        let coincident = PointSet::from_points(vec![point(0.5, 0.5), point(0.5, 0.5)]).unwrap();
+        assert_eq!(coincident.dedup(1e-3).len(), 1);
+
+        let set = PointSet::from_points(vec![
+            point(0.0, 0.0),
+            point(0.3, 0.3),
+            point(0.0005, -0.0005),
+            point(0.3, 0.3002),
+            point(-0.5, 0.5),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            set.dedup(1e-3),
+            PointSet::from_points(vec![point(0.0, 0.0), point(0.3, 0.3), point(-0.5, 0.5)])
+                .unwrap()
+        );
+        assert_eq!(set.dedup(0.0), set);
+        assert_ne!(set.dedup(1e-3), set);
+    }
+
+    #[test]
+    fn test_large_point_set() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let points: Vec<SNPoint> = (0..1000).map(|_| SNPoint::random(&mut rng)).collect();
+
+        let large = PointSet::new_large(
+            Arc::new(points.clone()),
+            PointSetGenerator::Explicit {
+                points: points.clone(),
+            },
+            1000,
+        );
+
+        assert_eq!(large.len(), 1000);
+        assert!(!large.is_byte_indexable());
+        assert_eq!(large[Byte::new(255)], points[255]);
+
+        let reloaded: PointSet =
+            serde_yaml::from_str(&serde_yaml::to_string(&large).unwrap()).unwrap();
+        assert_eq!(reloaded.len(), 1000);
+
+        // SNPoints serialize with four decimal places
+        assert!(reloaded
+            .points()
+            .iter()
+            .zip(&points)
+            .all(|(a, b)| a.approx_eq(*b, 1e-4)));
+
+        assert!(PointSet::from_points_large(points.clone(), 999).is_err());
+        assert_eq!(
+            PointSet::from_points_large(points, 1000).unwrap().len(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_explicit_point_cap_on_load() {
+        let saved = |count: usize| {
+            serde_yaml::to_string(&PointSetGenerator::Explicit {
+                points: vec![SNPoint::zero(); count],
+            })
+            .unwrap()
+        };
+
+        assert!(serde_yaml::from_str::<PointSet>(&saved(MAX_EXPLICIT_POINTS)).is_ok());
+        assert!(serde_yaml::from_str::<PointSet>(&saved(MAX_EXPLICIT_POINTS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_nearest_index_at_site() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let point_set = PointSetGenerator::UniformDistribution {
+                count: Byte::new(64),
+            }
+            .generate_point_set(&mut rng);
+
+            for (i, p) in point_set.points().iter().enumerate() {
+                assert_eq!(point_set.nearest_index(*p, DistanceFunction::Euclidean), i);
+                assert_eq!(point_set.nearest_index(*p, DistanceFunction::Chebyshev), i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_neighbour_graph_grid() {
+        let point_set = PointSetGenerator::UniformGrid {
+            x_count: Nibble::new(3),
+            y_count: Nibble::new(3),
+        }
+        .generate_point_set(&mut DeterministicRng::seed_from_u64(0));
+
+        let edges = point_set.nearest_neighbour_graph(Nibble::new(4));
+        let idx = |x: usize, y: usize| x * 4 + y;
+
+        for x in 1..3 {
+            for y in 1..3 {
+                for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                    let (a, b) = (idx(x, y), idx(nx, ny));
+                    assert!(edges.contains(&(a.min(b), a.max(b))));
+                }
+            }
+        }
+
+        assert!(edges.iter().all(|&(a, b)| a < b && b < point_set.len()));
+    }
+
+    #[test]
+    fn test_relative_neighbourhood_graph() {
+        let collinear = PointSet::from_points(vec![
+            SNPoint::from_snfloats(SNFloat::new(-0.5), SNFloat::ZERO),
+            SNPoint::from_snfloats(SNFloat::ZERO, SNFloat::ZERO),
+            SNPoint::from_snfloats(SNFloat::new(0.5), SNFloat::ZERO),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            collinear.relative_neighbourhood_graph(),
+            vec![(0, 1), (1, 2)]
+        );
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..10 {
+            let point_set = PointSet::random(&mut rng);
+
+            assert!(point_set
+                .relative_neighbourhood_graph()
+                .iter()
+                .all(|&(a, b)| a < b && b < point_set.len()));
+        }
+    }
+
+    #[test]
+    fn test_toroidal_poisson_spacing() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for radius in [0.1, 0.25, 0.5] {
+            let points = poisson(&mut rng, 255, radius, SFloatNormaliser::Sawtooth, true);
+
+            for (i, a) in points.iter().enumerate() {
+                for b in &points[i + 1..] {
+                    assert!(toroidal_distance(*a, *b) > radius);
+                }
+            }
+
+            assert!(points.iter().any(|p| p.x().into_inner() > 1.0 - radius));
+            assert!(points.iter().any(|p| p.x().into_inner() < radius - 1.0));
+        }
+
+        let generator = PointSetGenerator::PoissonTiling {
+            count: Byte::new(64),
+            radius: UNFloat::new(0.5),
+        };
+
+        assert!(generator.generate_point_set(&mut rng).len() > 1);
+    }
+
+    #[test]
+    fn test_rose_four_petals() {
+        let points = rose(64, 2, 1);
+
+        let radii: Vec<f32> = points
+            .iter()
+            .map(|p| p.into_inner().coords.norm())
+            .collect();
+        assert!(radii.iter().all(|&r| r <= 1.0 + 1e-6));
+
+        for tip in [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)] {
+            assert!(points
+                .iter()
+                .any(|p| distance(&p.into_inner(), &Point2::new(tip.0, tip.1)) < 1e-3));
+        }
+
+        // Petals meet at the origin between the axes
+        assert!(points
+            .iter()
+            .filter(|p| (p.x().into_inner().abs() - p.y().into_inner().abs()).abs() < 1e-3)
+            .all(|p| p.into_inner().coords.norm() < 1e-3));
+
+        assert_eq!(rose(64, 4, 2), points);
+    }
+
+    #[test]
+    fn test_sparse_grid_single_cell() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for (x_mod, y_mod) in [(false, false), (false, true), (true, false), (true, true)] {
+            let point_set = PointSetGenerator::SparseGrid {
+                x_count: Nibble::new(0),
+                y_count: Nibble::new(0),
+                x_mod: Boolean::new(x_mod),
+                y_mod: Boolean::new(y_mod),
+            }
+            .generate_point_set(&mut rng);
+
+            assert_eq!(point_set.points(), &[SNPoint::zero()]);
+        }
+    }
+
+    #[test]
+    fn test_superformula_degenerate_parameters() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for (count, m, n) in [(0, 0, 0.0), (1, 15, 1.0), (255, 0, 1.0), (255, 15, 0.0)] {
+            let generator = PointSetGenerator::Superformula {
+                count: Byte::new(count),
+                m: Nibble::new(m),
+                n1: UNFloat::new(n),
+                n2: UNFloat::new(n),
+                n3: UNFloat::new(1.0 - n),
+            };
+            let point_set = generator.generate_point_set(&mut rng);
+
+            assert!(!point_set.is_empty());
+            assert!(point_set.points().iter().all(|p| {
+                let p = p.into_inner();
+
+                p.x.is_finite() && p.y.is_finite() && p.coords.norm() <= 1.0 + 1e-6
+            }));
+        }
+
+        // m = 0 with equal exponents is a circle
+        let circle = superformula(32, 0.0, 1.0, 1.0, 1.0);
+        assert!(circle
+            .iter()
+            .all(|p| (p.into_inner().coords.norm() - 1.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_generator_mutation_preserves_variant() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let trials = 1000;
+        let (mut preserved, mut changed) = (0, 0);
+
+        for _ in 0..trials {
+            let (x, y) = (Nibble::random(&mut rng), Nibble::random(&mut rng));
+            let mut generator = PointSetGenerator::UniformGrid {
+                x_count: x,
+                y_count: y,
+            };
+            generator.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+
+            if let PointSetGenerator::UniformGrid { x_count, y_count } = generator {
+                preserved += 1;
+
+                if (x, y) != (x_count, y_count) {
+                    changed += 1;
+                }
+            }
+        }
+
+        assert!(preserved > trials * 8 / 10);
+        assert!(changed > preserved / 2);
+
+        let mut point_set = PointSet::random(&mut rng);
+        for _ in 0..100 {
+            point_set.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+            assert!(!point_set.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_apply_matrix() {
+        let moore =
+            PointSetGenerator::Moore.generate_point_set(&mut DeterministicRng::seed_from_u64(0));
+        let quarter_turn = SNFloatMatrix3::new_rotation(Angle::new_unchecked(PI / 2.0));
+
+        let rotated = moore.apply_matrix(quarter_turn, SFloatNormaliser::Clamp);
+        assert!(matches!(rotated.generator, PointSetGenerator::Moore));
+        assert_eq!(rotated.len(), moore.len());
+        assert!(rotated
+            .points()
+            .iter()
+            .all(|p| moore.points().iter().any(|q| q.approx_eq(*p, 1e-5))));
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let stretch = SNFloatMatrix3::new_translation(SNFloat::new(0.9), SNFloat::new(-0.7))
+            .multiply(SNFloatMatrix3::new_scaling(
+                SNFloat::new(-1.0),
+                SNFloat::new(1.0),
+            ))
+            .multiply(quarter_turn);
+
+        for normaliser in [
+            SFloatNormaliser::Sawtooth,
+            SFloatNormaliser::Triangle,
+            SFloatNormaliser::Sin,
+            SFloatNormaliser::SinRepeating,
+            SFloatNormaliser::TanH,
+            SFloatNormaliser::Clamp,
+            SFloatNormaliser::Fractional,
+            SFloatNormaliser::Random,
+        ] {
+            for m in [quarter_turn, stretch] {
+                let transformed = PointSet::random(&mut rng).apply_matrix(m, normaliser);
+
+                assert!(transformed.points().iter().all(|p| {
+                    let p = p.into_inner();
+                    (-1.0..=1.0).contains(&p.x) && (-1.0..=1.0).contains(&p.y)
+                }));
+            }
+        }
+    }
+
+    #[test]
+    fn test_stochastic_generators_reload_identically() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for generator in [
+            PointSetGenerator::Poisson {
+                count: Byte::new(64),
+                radius: UNFloat::new(0.1),
+            },
+            PointSetGenerator::UniformDistribution {
+                count: Byte::new(64),
+            },
+        ] {
+            let original = generator.generate_point_set(&mut rng);
+
+            let serialized = serde_yaml::to_string(&original).unwrap();
+            let reloaded: PointSet = serde_yaml::from_str(&serialized).unwrap();
+
+            assert_eq!(reloaded.seed(), original.seed());
+            assert_eq!(reloaded.points(), original.points());
+            assert_eq!(serde_yaml::to_string(&reloaded).unwrap(), serialized);
+
+            let weighted = generator.generate_weighted_point_set(&mut rng);
+            let reloaded: WeightedPointSet =
+                serde_yaml::from_str(&serde_yaml::to_string(&weighted).unwrap()).unwrap();
+            assert_eq!(reloaded.points(), weighted.points());
+        }
+
+        // Sets saved as a bare generator still load
+        let legacy: PointSet = serde_yaml::from_str(
+            &serde_yaml::to_string(&PointSetGenerator::UniformDistribution {
+                count: Byte::new(10),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(legacy.len(), 10);
+    }
+
+    #[test]
+    fn test_fibonacci_spiral() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for count in [0, 1, 2, 13, 100, 255] {
+            for scalar in [0.0, 0.5, 1.0] {
+                let generator = PointSetGenerator::FibonacciSpiral {
+                    count: Byte::new(count),
+                    scalar: UNFloat::new(scalar),
+                };
+                let point_set = generator.generate_point_set(&mut rng);
+
+                assert_eq!(point_set.len(), usize::from(count).max(1));
+                assert!(point_set.points().iter().all(|p| {
+                    let p = p.into_inner();
+                    (-1.0..=1.0).contains(&p.x) && (-1.0..=1.0).contains(&p.y)
+                }));
+
+                for (i, a) in point_set.points().iter().enumerate() {
+                    for b in &point_set.points()[i + 1..] {
+                        assert!(!a.approx_eq(*b, POINT_EPSILON));
+                    }
+                }
+
+                let reloaded: PointSet =
+                    serde_yaml::from_str(&serde_yaml::to_string(&point_set).unwrap()).unwrap();
+                assert_eq!(reloaded.points(), point_set.points());
+            }
+        }
+    }
+
+    #[test]
+    fn test_worley_features_are_seeded() {
+        let generate = |seed: u32| {
+            PointSetGenerator::WorleyFeatures {
+                count: Byte::new(50),
+                seed: SeedParams { seed },
+            }
+            .generate_point_set(&mut DeterministicRng::seed_from_u64(0))
+        };
+
+        assert_eq!(generate(7).points(), generate(7).points());
+        assert_ne!(generate(7).points(), generate(8).points());
+
+        // One point per cell of an 8x7 grid
+        let points = generate(7);
+        assert_eq!(points.len(), 50);
+
+        let mut cells: Vec<(usize, usize)> = points
+            .points()
+            .iter()
+            .map(|p| {
+                let p = p.into_inner();
+                (
+                    ((p.x + 1.0) * 4.0).min(7.0) as usize,
+                    ((p.y + 1.0) * 3.5).min(6.0) as usize,
+                )
+            })
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), 50);
+
+        let reloaded: PointSet =
+            serde_yaml::from_str(&serde_yaml::to_string(&points).unwrap()).unwrap();
+        assert_eq!(reloaded.points(), points.points());
+    }
+
+    #[test]
+    fn test_jitter_keeps_structure() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let original = PointSet::random(&mut rng);
+            let mut jittered = original.clone();
+
+            jittered.jitter(&mut rng, UNFloat::new(0.05));
+
+            assert_eq!(jittered.len(), original.len());
+            assert_eq!(
+                serde_json::to_string(&jittered.generator).unwrap(),
+                serde_json::to_string(&original.generator).unwrap()
+            );
+            assert!(jittered
+                .points()
+                .iter()
+                .zip(original.points())
+                .any(|(a, b)| a.into_inner() != b.into_inner()));
+            assert!(jittered
+                .points()
+                .iter()
+                .zip(original.points())
+                .all(|(a, b)| distance(&a.into_inner(), &b.into_inner()) <= 0.05 * SQRT_2 + 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_jitter_explicit_serializes() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let points = (0..16).map(|_| SNPoint::random(&mut rng)).collect();
+        let mut point_set = PointSet::from_points(points).unwrap();
+
+        point_set.jitter(&mut rng, UNFloat::new(0.05));
+
+        let reloaded: PointSet =
+            serde_yaml::from_str(&serde_yaml::to_string(&point_set).unwrap()).unwrap();
+
+        // SNPoints serialize with four decimal places
+        assert!(reloaded
+            .points()
+            .iter()
+            .zip(point_set.points())
+            .all(|(a, b)| a.approx_eq(*b, 1e-4)));
+    }
+
+    #[test]
+    fn test_mutated_point_set_reloads() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..200 {
+            let mut point_set = PointSet::random(&mut rng);
+            point_set.mutate_rng(&mut rng, ProtoMutArg::new(&mut None));
+
+            let reloaded: PointSet =
+                serde_json::from_str(&serde_json::to_string(&point_set).unwrap()).unwrap();
+
+            // SNPoints serialize with four decimal places
+            assert_eq!(reloaded.len(), point_set.len());
+            assert!(reloaded
+                .points()
+                .iter()
+                .zip(point_set.points())
+                .all(|(a, b)| a.approx_eq(*b, 1e-4)));
+        }
+    }
+
+    #[test]
+    fn test_color_by_noise() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let ramp = ColorRamp::new(
+            vec![
+                ColorStop {
+                    position: UNFloat::ZERO,
+                    color: FloatColor::BLACK,
+                },
+                ColorStop {
+                    position: UNFloat::ONE,
+                    color: FloatColor::WHITE,
+                },
+            ],
+            ColorRampInterpolation::Linear,
+        );
+
+        let point_set = PointSetGenerator::UniformDistribution {
+            count: Byte::new(64),
+        }
+        .generate_point_set(&mut rng);
+
+        // A curve through a single control point flattens any base noise to that point's output
+        let base = NoiseFunctions::generate_rng(
+            &mut rng,
+            ProtoGenArg {
+                profiler: &mut None,
+            },
+        );
+        let constant = NoiseFunctions::Curve(NoiseCurve::new(
+            Box::new(base),
+            vec![(SNFloat::ZERO, SNFloat::new(0.5))],
+        ));
+
+        let colors = point_set.color_by_noise(&constant, &ramp, 0.0);
+
+        assert_eq!(colors.len(), point_set.len());
+        assert!(colors.iter().all(|&c| c == ramp.sample(UNFloat::new(0.75))));
+    }
+
+    #[test]
+    fn test_closest_point_ignores_near_identical() {
+        let p = SNPoint::from_snfloats(SNFloat::new(0.25), SNFloat::new(-0.5));
+        let nudged = SNPoint::from_snfloats(SNFloat::new(0.25 + 1e-7), SNFloat::new(-0.5));
+        let near = SNPoint::from_snfloats(SNFloat::new(0.3), SNFloat::new(-0.5));
+        let far = SNPoint::from_snfloats(SNFloat::new(-0.9), SNFloat::new(0.9));
+
+        assert!(p.approx_eq(nudged, POINT_EPSILON));
+        assert!(!p.approx_eq(near, POINT_EPSILON));
+
+        let point_set = PointSet::from_points(vec![nudged, near, far]).unwrap();
+
+        assert_eq!(point_set.get_closest_point(p), near);
+
+        let point_set = PointSet::from_points(vec![nudged, near]).unwrap();
+
+        assert_eq!(point_set.get_furthest_point(p), near);
+    }
+
+    #[test]
+    fn test_snap_point() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let set = PointSet::random(&mut rng);
+
+        for &p in set.points() {
+            assert_eq!(set.snap_point(p, DistanceFunction::Euclidean), p);
+        }
+
+        let p = SNPoint::random(&mut rng);
+        let snapped = set.snap_point(p, DistanceFunction::Chebyshev);
+        let d = |q: SNPoint| {
+            DistanceFunction::Chebyshev.calculate_point2(q.into_inner(), p.into_inner())
+        };
+
+        assert!(set.points().iter().all(|&q| d(snapped) <= d(q)));
+    }
+
+    #[test]
+    fn test_enclosing_circle() {
+        let point = |x: f32, y: f32| SNPoint::from_snfloats(SNFloat::new(x), SNFloat::new(y));
+
+        let square = PointSet::from_points(vec![
+            point(-0.5, -0.5),
+            point(0.5, -0.5),
+            point(0.5, 0.5),
+            point(-0.5, 0.5),
+        ])
+        .unwrap();
+
+        let (center, radius) = square.enclosing_circle();
+        assert!(center.approx_eq(SNPoint::zero(), 1e-5));
+        assert!((radius - 0.5 * SQRT_2).abs() < 1e-5);
+
+        let single = PointSet::from_points(vec![point(0.3, 0.2)]).unwrap();
+        assert_eq!(single.enclosing_circle(), (point(0.3, 0.2), 0.0));
+
+        let pair = PointSet::from_points(vec![point(-1.0, 0.0), point(1.0, 0.0)]).unwrap();
+        let (center, radius) = pair.enclosing_circle();
+        assert!(center.approx_eq(SNPoint::zero(), 1e-6));
+        assert!((radius - 1.0).abs() < 1e-6);
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..50 {
+            let set = PointSet::random(&mut rng);
+            let (center, radius) = set.enclosing_circle();
+
+            assert!(set
+                .points()
+                .iter()
+                .all(|p| distance(&p.into_inner(), &center.into_inner()) <= radius + 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_get_n_closest_points_tie_break() {
+        let points: Vec<SNPoint> = [(0.5, 0.0), (0.0, 0.5), (-0.5, 0.0), (0.0, -0.5), (0.9, 0.9)]
+            .iter()
+            .map(|&(x, y)| SNPoint::from_snfloats(SNFloat::new(x), SNFloat::new(y)))
+            .collect();
+
+        let mut reversed = points.clone();
+        reversed.reverse();
+
+        let mut a = PointSet::from_points(points).unwrap();
+        let mut b = PointSet::from_points(reversed).unwrap();
+
+        for n in 1..=5 {
+            assert_eq!(
+                a.get_n_closest_points(SNPoint::zero(), n).to_vec(),
+                b.get_n_closest_points(SNPoint::zero(), n).to_vec()
+            );
+        }
+
+        assert_eq!(
+            a.get_n_closest_points(SNPoint::zero(), 2),
+            &[
+                SNPoint::from_snfloats(SNFloat::new(-0.5), SNFloat::ZERO),
+                SNPoint::from_snfloats(SNFloat::ZERO, SNFloat::new(-0.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symmetrize() {
+        let point_set = PointSet::from_points(vec![SNPoint::from_snfloats(
+            SNFloat::new(0.5),
+            SNFloat::ZERO,
+        )])
+        .unwrap();
+
+        let symmetric = point_set.symmetrize(Nibble::new(4));
+        let expected = [(0.5, 0.0), (0.0, 0.5), (-0.5, 0.0), (0.0, -0.5)];
+
+        assert_eq!(symmetric.len(), 4);
+
+        for (p, (x, y)) in symmetric.points().iter().zip(expected) {
+            assert!(p.approx_eq(
+                SNPoint::from_snfloats(SNFloat::new(x), SNFloat::new(y)),
+                1e-6
+            ));
+        }
+
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let folds = Nibble::random(&mut rng);
+            let symmetric = PointSet::random(&mut rng).symmetrize(folds);
+
+            assert!(symmetric.len() <= 256);
+            assert_eq!(symmetric.len() % usize::from(folds.into_inner().max(1)), 0);
+        }
+    }
+
+    #[test]
+    fn test_to_sdf() {
+        let a = SNPoint::from_snfloats(SNFloat::new(-0.75), SNFloat::new(-0.75));
+        let b = SNPoint::from_snfloats(SNFloat::new(0.25), SNFloat::new(0.75));
+        let point_set = PointSet::from_points(vec![a, b]).unwrap();
+
+        for distance_function in [
+            DistanceFunction::Euclidean,
+            DistanceFunction::Manhattan,
+            DistanceFunction::Chebyshev,
+        ] {
+            let sdf = point_set.to_sdf(4, 4, distance_function, UFloatNormaliser::Clamp);
+
+            assert!(sdf[Point2::new(0, 0)].into_inner() < 1e-5);
+            assert!(sdf[Point2::new(2, 3)].into_inner() < 1e-5);
+            assert!(sdf[Point2::new(3, 0)].into_inner() > 0.1);
+        }
+    }
+
+    #[test]
+    fn test_weighted_point_set_serde() {
+        let generator = PointSetGenerator::PackedCircles {
+            count: Byte::new(32),
+            min_radius: UNFloat::new(0.05),
+            max_radius: UNFloat::new(0.2),
+        };
+
+        let a = generator.generate_weighted_point_set(&mut DeterministicRng::seed_from_u64(0));
+        let serialized = serde_yaml::to_string(&a).unwrap();
+        let b: WeightedPointSet = serde_yaml::from_str(&serialized).unwrap();
+
+        assert_eq!(serialized, serde_yaml::to_string(&b).unwrap());
+        assert_eq!(b.points().len(), b.weights().len());
+        assert!(!b.is_empty() && b.len() <= 32);
+    }
+}
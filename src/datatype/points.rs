@@ -15,7 +15,7 @@ use serde::{
 };
 
 use crate::{
-    datatype::{complex::*, constraint_resolvers::*, continuous::*},
+    datatype::{complex::*, constraint_resolvers::*, continuous::*, discrete::*, matrices::*},
     mutagen_args::*,
 };
 
@@ -29,11 +29,13 @@ impl SNPoint {
         Self { value }
     }
 
+    #[track_caller]
     pub fn new(value: Point2<f32>) -> Self {
         assert!(
-            value.x >= -1.0 && value.y <= 1.0 && value.x >= -1.0 && value.y <= 1.0,
-            "Invalid SNPoint value: {}",
-            value
+            value.x >= -1.0 && value.x <= 1.0 && value.y >= -1.0 && value.y <= 1.0,
+            "Invalid SNPoint value: ({}, {})",
+            value.x,
+            value.y
         );
 
         Self::new_unchecked(value)
@@ -78,6 +80,11 @@ impl SNPoint {
         self.value
     }
 
+    /// True if both coordinates are within epsilon of the other point's
+    pub fn approx_eq(self, other: Self, epsilon: f32) -> bool {
+        self.x().approx_eq(other.x(), epsilon) && self.y().approx_eq(other.y(), epsilon)
+    }
+
     pub fn x(self) -> SNFloat {
         SNFloat::new_unchecked(self.value.x)
     }
@@ -98,6 +105,55 @@ impl SNPoint {
         Self::new(Point2::from(self.into_inner().coords + other.into_inner().coords) * 0.5)
     }
 
+    /// Transforms the point as (x, y, 1), dividing through by the resulting w unless it's zero,
+    /// then brings each coordinate back into range with the normaliser
+    pub fn apply_matrix(self, m: SNFloatMatrix3, normaliser: SFloatNormaliser) -> Self {
+        let h = m.into_inner() * self.value.to_homogeneous();
+        let w = if h.z.abs() > f32::EPSILON { h.z } else { 1.0 };
+
+        Self::new_normalised(Point2::new(h.x / w, h.y / w), normaliser)
+    }
+
+    pub fn rotate_around(
+        self,
+        center: SNPoint,
+        theta: Angle,
+        normaliser: SFloatNormaliser,
+    ) -> Self {
+        let m = SNFloatMatrix3::new_translation(center.x(), center.y())
+            .multiply(SNFloatMatrix3::new_rotation(theta))
+            .multiply(SNFloatMatrix3::new_translation(
+                center.x().invert(),
+                center.y().invert(),
+            ));
+
+        self.apply_matrix(m, normaliser)
+    }
+
+    /// Closest point on the infinite line through origin at direction, clamped back into range
+    pub fn project_onto_line(self, origin: SNPoint, direction: Angle) -> Self {
+        let direction = Vector2::new(direction.into_inner().cos(), direction.into_inner().sin());
+        let along = (self.into_inner() - origin.into_inner()).dot(&direction);
+
+        Self::new_normalised(
+            origin.into_inner() + direction * along,
+            SFloatNormaliser::Clamp,
+        )
+    }
+
+    /// Rounds each coordinate to the nearest of divisions + 1 evenly spaced positions spanning [-1, 1].
+    /// Zero divisions is treated as one.
+    pub fn snap_to_grid(self, divisions: Nibble) -> Self {
+        let divisions = f32::from(divisions.into_inner().max(1));
+        let snap = |v: SNFloat| {
+            SNFloat::new_clamped(
+                ((v.into_inner() + 1.0) * 0.5 * divisions).round() / divisions * 2.0 - 1.0,
+            )
+        };
+
+        Self::from_snfloats(snap(self.x()), snap(self.y()))
+    }
+
     pub fn invert_x(self) -> Self {
         Self::from_snfloats(self.x().invert(), self.y())
     }
@@ -131,39 +187,22 @@ impl SNPoint {
         SNPoint::from_snfloats(self.x().multiply(other.x()), self.y().multiply(other.y()))
     }
 
+    /// Packs this point's polar form into a point, with theta along x and rho along y
     pub fn to_polar(self) -> Self {
-        //Represents the angle from 0.0..2PI
-        //atan2(y, x) is correct, but it's more visually appealing to have the axis of symmetry along the vertical axis
-        //Sorry if this makes me a bad person :<
-        let theta = Angle::new(f32::atan2(-self.value.x, self.value.y));
-        //Represents the radius between 0.0..1.0
-        let rho = UNFloat::new(f32::sqrt(self.value.x.powf(2.0) + self.value.y.powf(2.0)).min(1.0));
+        let polar = SNPolarPoint::from_cartesian(self);
 
-        Self::from_snfloats(theta.to_signed(), rho.to_signed())
+        Self::from_snfloats(polar.theta.to_signed(), polar.rho.to_signed())
     }
 
-    // TODO Refactor this when polar point datatype is added
+    /// Unpacks a point produced by to_polar back into cartesian form
     #[allow(clippy::wrong_self_convention)]
     pub fn from_polar(self) -> Self {
-        let theta = self.x().to_angle().into_inner();
-        let rho = self.y().to_unsigned().into_inner();
-
-        Self::from_snfloats(
-            SNFloat::new(rho * f32::sin(theta)),
-            SNFloat::new(rho * f32::cos(theta)),
-        )
+        SNPolarPoint::new(self.y().to_unsigned(), self.x().to_angle()).to_cartesian()
     }
 
-    // TODO Refactor this when polar point datatype is added
     #[allow(clippy::wrong_self_convention)]
     pub fn from_polar_components(theta: Angle, rho: UNFloat) -> Self {
-        let theta = theta.into_inner();
-        let rho = rho.into_inner();
-
-        Self::from_snfloats(
-            SNFloat::new(rho * f32::sin(theta)),
-            SNFloat::new(rho * f32::cos(theta)),
-        )
+        SNPolarPoint::new(rho, theta).to_cartesian()
     }
 
     // TODO Refactor this when polar point datatype is added
@@ -264,225 +303,82 @@ impl<'a> UpdatableRecursively<'a> for SNPoint {
     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
 }
 
-// #[derive(Clone, Copy, Debug, PartialEq)]
-// pub struct SNPolarPoint {
-//     rho: SNFloat,
-//     theta: Angle,
-// }
-
-// impl SNPolarPoint {
-//     pub fn new_unchecked(value: Point2<f32>) -> Self {
-//         Self { value }
-//     }
-
-//     pub fn new(value: Point2<f32>) -> Self {
-//         assert!(
-//             value.x >= -1.0 && value.y <= 1.0 && value.x >= -1.0 && value.y <= 1.0,
-//             "Invalid SNPolarPoint value: {}",
-//             value
-//         );
-
-//         Self::new_unchecked(value)
-//     }
-
-//     pub fn new_normalised(value: Point2<f32>, normaliser: SFloatNormaliser) -> Self {
-//         Self::from_snfloats(normaliser.normalise(value.x), normaliser.normalise(value.y))
-//     }
-
-//     // TODO Figure out what this does, if possible replace distance function with distance function enum
-//     pub fn subtract_normalised(&self, other: SNPolarPoint) -> Self {
-//         let result = self.into_inner() - other.into_inner();
-//         Self::new(
-//             Point2::new(result.x, result.y)
-//                 / distance(&self.into_inner(), &other.into_inner()).max(0.1),
-//         )
-//     }
-
-//     pub fn from_snfloats(x: SNFloat, y: SNFloat) -> Self {
-//         Self::new_unchecked(Point2::new(x.into_inner(), y.into_inner()))
-//     }
-
-//     pub fn zero() -> Self {
-//         Self::new(Point2::origin())
-//     }
-
-//     pub fn into_inner(self) -> Point2<f32> {
-//         self.value
-//     }
-
-//     pub fn x(self) -> SNFloat {
-//         SNFloat::new_unchecked(self.value.x)
-//     }
-
-//     pub fn y(self) -> SNFloat {
-//         SNFloat::new_unchecked(self.value.y)
-//     }
-
-//     pub fn abs(self) -> Self {
-//         Self::from_snfloats(self.x().abs(), self.y().abs())
-//     }
-
-//     pub fn to_angle(self) -> Angle {
-//         Angle::new(f32::atan2(self.value.x, self.value.y))
-//     }
-
-//     pub fn average(self, other: Self) -> Self {
-//         Self::new(Point2::from(self.into_inner().coords + other.into_inner().coords) * 0.5)
-//     }
-
-//     pub fn invert_x(self) -> Self {
-//         Self::from_snfloats(self.x().invert(), self.y())
-//     }
-
-//     pub fn normalised_add(self, other: SNPolarPoint, normaliser: SFloatNormaliser) -> SNPolarPoint {
-//         SNPolarPoint::from_snfloats(
-//             self.x().normalised_add(other.x(), normaliser),
-//             self.y().normalised_add(other.y(), normaliser),
-//         )
-//     }
-
-//     pub fn normalised_sub(self, other: SNPolarPoint, normaliser: SFloatNormaliser) -> SNPolarPoint {
-//         SNPolarPoint::from_snfloats(
-//             self.x().normalised_sub(other.x(), normaliser),
-//             self.y().normalised_sub(other.y(), normaliser),
-//         )
-//     }
-
-//     pub fn scale(self, other: SNFloat) -> SNPolarPoint {
-//         SNPolarPoint::from_snfloats(self.x().multiply(other), self.y().multiply(other))
-//     }
-
-//     pub fn scale_unfloat(self, other: UNFloat) -> SNPolarPoint {
-//         SNPolarPoint::from_snfloats(
-//             self.x().multiply_unfloat(other),
-//             self.y().multiply_unfloat(other),
-//         )
-//     }
-
-//     pub fn scale_point(self, other: SNPolarPoint) -> SNPolarPoint {
-//         SNPolarPoint::from_snfloats(self.x().multiply(other.x()), self.y().multiply(other.y()))
-//     }
-
-//     pub fn to_polar(self) -> Self {
-//         //Represents the angle from 0.0..2PI
-//         //atan2(y, x) is correct, but it's more visually appealing to have the axis of symmetry along the vertical axis
-//         //Sorry if this makes me a bad person :<
-//         let theta = Angle::new(f32::atan2(-self.value.x, self.value.y));
-//         //Represents the radius between 0.0..1.0
-//         let rho = UNFloat::new(f32::sqrt(self.value.x.powf(2.0) + self.value.y.powf(2.0)).min(1.0));
-
-//         Self::from_snfloats(theta.to_signed(), rho.to_signed())
-//     }
-
-//     // TODO Refactor this when polar point datatype is added
-//     #[allow(clippy::wrong_self_convention)]
-//     pub fn from_polar(self) -> Self {
-//         let theta = self.x().to_angle().into_inner();
-//         let rho = self.y().to_unsigned().into_inner();
-
-//         Self::from_snfloats(
-//             SNFloat::new(rho * f32::sin(theta)),
-//             SNFloat::new(rho * f32::cos(theta)),
-//         )
-//     }
-
-//     // TODO Refactor this when polar point datatype is added
-//     pub fn from_complex(value: SNComplex) -> Self {
-//         Self::from_snfloats(value.re(), value.im())
-//     }
-
-//     pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
-//         Self::new(Point2::new(
-//             rng.gen_range(-1.0..=1.0),
-//             rng.gen_range(-1.0..=1.0),
-//         ))
-//     }
-// }
-
-// impl Serialize for SNPolarPoint {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: Serializer,
-//     {
-//         self.to_string().serialize(serializer)
-//     }
-// }
-
-// impl<'de> Deserialize<'de> for SNPolarPoint {
-//     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-//     where
-//         D: Deserializer<'de>,
-//     {
-//         deserializer.deserialize_str(SNPolarPointVisitor)
-//     }
-// }
-
-// struct SNPolarPointVisitor;
-
-// impl<'de> Visitor<'de> for SNPolarPointVisitor {
-//     type Value = SNPolarPoint;
-
-//     fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-//         formatter.write_str("a point like '(0.0, 0.0)'")
-//     }
-
-//     fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
-//         lazy_static! {
-//             static ref RE: Regex =
-//                 Regex::new(r#"\(\s*(-?[\d\.]+)\s*,\s*(-?[\d\.]+)\s*\)"#).unwrap();
-//         }
-
-//         let caps = RE
-//             .captures(v)
-//             .ok_or_else(|| E::custom(format!("Invalid point: {}", v)))?;
-
-//         let x = f32::from_str(&caps[1]).map_err(|e| E::custom(e.to_string()))?;
-//         let y = f32::from_str(&caps[2]).map_err(|e| E::custom(e.to_string()))?;
-
-//         if x < -1.0 || x > 1.0 || y < -1.0 || y > 1.0 {
-//             return Err(E::custom(format!("SNPolarPoint out of range: {}", v)));
-//         }
-
-//         Ok(SNPolarPoint::new(Point2::new(x, y)))
-//     }
-// }
-
-// impl Display for SNPolarPoint {
-//     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-//         write!(f, "({}, {})", self.x(), self.y())
-//     }
-// }
-
-// impl Default for SNPolarPoint {
-//     fn default() -> Self {
-//         Self::new(Point2::new(f32::default(), f32::default()))
-//     }
-// }
-
-// impl<'a> Generatable<'a> for SNPolarPoint {
-//     type GenArg = ProtoGenArg<'a>;
-
-//     fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
-//         Self::random(rng)
-//     }
-// }
-
-// impl<'a> Mutatable<'a> for SNPolarPoint {
-//     type MutArg = ProtoMutArg<'a>;
-//     fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, _arg: ProtoMutArg<'a>) {
-//         *self = Self::random(rng);
-//     }
-// }
-
-// impl<'a> Updatable<'a> for SNPolarPoint {
-//     type UpdateArg = ProtoUpdArg<'a>;
-
-//     fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
-// }
-
-// impl<'a> UpdatableRecursively<'a> for SNPolarPoint {
-//     fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
-// }
+/// A point in polar form, with theta measured from the positive y axis towards positive x
+/// so that the axis of symmetry is vertical. Radii past the unit circle are clamped.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SNPolarPoint {
+    pub rho: UNFloat,
+    pub theta: Angle,
+}
+
+impl SNPolarPoint {
+    pub fn new(rho: UNFloat, theta: Angle) -> Self {
+        Self { rho, theta }
+    }
+
+    pub fn from_cartesian(point: SNPoint) -> Self {
+        let p = point.into_inner();
+
+        Self::new(
+            UNFloat::new_clamped(p.coords.norm()),
+            Angle::new_wrapped(f32::atan2(p.x, p.y)),
+        )
+    }
+
+    pub fn to_cartesian(self) -> SNPoint {
+        let theta = self.theta.into_inner();
+        let rho = self.rho.into_inner();
+
+        SNPoint::from_snfloats(
+            SNFloat::new_clamped(rho * f32::sin(theta)),
+            SNFloat::new_clamped(rho * f32::cos(theta)),
+        )
+    }
+
+    pub fn rotate(self, by: Angle) -> Self {
+        Self::new(
+            self.rho,
+            Angle::new_wrapped(self.theta.into_inner() + by.into_inner()),
+        )
+    }
+
+    pub fn scale_rho(self, by: UNFloat) -> Self {
+        Self::new(self.rho.multiply(by), self.theta)
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(UNFloat::random(rng), Angle::random(rng))
+    }
+}
+
+impl<'a> Generatable<'a> for SNPolarPoint {
+    type GenArg = ProtoGenArg<'a>;
+
+    fn generate_rng<R: Rng + ?Sized>(rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+        Self::random(rng)
+    }
+}
+
+impl<'a> Mutatable<'a> for SNPolarPoint {
+    type MutArg = ProtoMutArg<'a>;
+    fn mutate_rng<R: Rng + ?Sized>(&mut self, rng: &mut R, arg: ProtoMutArg<'a>) {
+        if rng.gen() {
+            self.rho.mutate_rng(rng, arg);
+        } else {
+            self.theta.mutate_rng(rng, arg);
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for SNPolarPoint {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
+
+impl<'a> UpdatableRecursively<'a> for SNPolarPoint {
+    fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+}
 
 #[cfg(test)]
 mod tests {
@@ -494,4 +390,104 @@ mod tests {
         let b: SNPoint = serde_yaml::from_str(&serde_yaml::to_string(&a).unwrap()).unwrap();
         assert_eq!(a, b);
     }
+
+    #[test]
+    #[should_panic(expected = "Invalid SNPoint value: (2, 0)")]
+    fn test_snpoint_new_rejects_large_x() {
+        SNPoint::new(Point2::new(2.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid SNPoint value: (0, -2)")]
+    fn test_snpoint_new_rejects_small_y() {
+        SNPoint::new(Point2::new(0.0, -2.0));
+    }
+
+    #[test]
+    fn test_polar_round_trip() {
+        for y in -10..=10 {
+            for x in -10..=10 {
+                let p = SNPoint::new(Point2::new(x as f32 / 10.0, y as f32 / 10.0));
+
+                if p.into_inner().coords.norm() > 1.0 {
+                    continue;
+                }
+
+                let polar = SNPolarPoint::from_cartesian(p);
+                assert!(polar.to_cartesian().approx_eq(p, 1e-5), "{}", p);
+                assert!(p.to_polar().from_polar().approx_eq(p, 1e-5), "{}", p);
+                assert!(
+                    SNPoint::from_polar_components(polar.theta, polar.rho).approx_eq(p, 1e-5),
+                    "{}",
+                    p
+                );
+            }
+        }
+
+        let up = SNPolarPoint::new(UNFloat::ONE, Angle::ZERO);
+        assert!(up
+            .to_cartesian()
+            .approx_eq(SNPoint::new(Point2::new(0.0, 1.0)), 1e-6));
+        assert!(up
+            .rotate(Angle::new_unchecked(std::f32::consts::FRAC_PI_2))
+            .scale_rho(UNFloat::new(0.5))
+            .to_cartesian()
+            .approx_eq(SNPoint::new(Point2::new(0.5, 0.0)), 1e-6));
+
+        let serialized = serde_yaml::to_string(&up).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<SNPolarPoint>(&serialized).unwrap(),
+            up
+        );
+    }
+
+    #[test]
+    fn test_rotate_around() {
+        let center = SNPoint::new(Point2::new(0.5, 0.5));
+        let p = SNPoint::new(Point2::new(0.75, 0.5));
+
+        let rotated = p.rotate_around(
+            center,
+            Angle::new_unchecked(std::f32::consts::FRAC_PI_2),
+            SFloatNormaliser::Clamp,
+        );
+        assert!(rotated.approx_eq(SNPoint::new(Point2::new(0.5, 0.75)), 1e-6));
+
+        let scaled = p.apply_matrix(
+            SNFloatMatrix3::new_scaling(SNFloat::new(0.5), SNFloat::new(-1.0)),
+            SFloatNormaliser::Clamp,
+        );
+        assert!(scaled.approx_eq(SNPoint::new(Point2::new(0.375, -0.5)), 1e-6));
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let p = SNPoint::new(Point2::new(0.3, -0.62));
+
+        let fine = p.snap_to_grid(Nibble::new(15));
+        assert!(fine.approx_eq(p, 1.0 / 15.0));
+
+        assert_eq!(
+            p.snap_to_grid(Nibble::new(1)),
+            SNPoint::new(Point2::new(1.0, -1.0))
+        );
+        assert_eq!(
+            p.snap_to_grid(Nibble::new(2)),
+            SNPoint::new(Point2::new(0.0, -1.0))
+        );
+    }
+
+    #[test]
+    fn test_project_onto_line() {
+        let p = SNPoint::new(Point2::new(0.3, 0.7));
+
+        let on_x_axis = p.project_onto_line(SNPoint::zero(), Angle::ZERO);
+        assert!(on_x_axis.approx_eq(SNPoint::new(Point2::new(0.3, 0.0)), 1e-6));
+
+        let on_diagonal = p.project_onto_line(
+            SNPoint::new(Point2::new(-0.5, -0.5)),
+            Angle::new_unchecked(std::f32::consts::FRAC_PI_4),
+        );
+        assert!(on_diagonal.approx_eq(SNPoint::new(Point2::new(0.5, 0.5)), 1e-6));
+    }
 }
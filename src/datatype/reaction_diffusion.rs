@@ -0,0 +1,266 @@
+use failure::{ensure, Fallible};
+use mutagen::{Updatable, UpdatableRecursively};
+use nalgebra::*;
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A Gray-Scott reaction-diffusion system over two concentration buffers that wrap at the edges.
+/// Chemical A is fed in at the feed rate, B is removed at feed + kill, and A + 2B -> 3B.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(try_from = "GrayScottFields")]
+pub struct GrayScott {
+    pub feed: UNFloat,
+    pub kill: UNFloat,
+    pub d_a: UNFloat,
+    pub d_b: UNFloat,
+    a: Buffer<UNFloat>,
+    b: Buffer<UNFloat>,
+}
+
+#[derive(Deserialize)]
+struct GrayScottFields {
+    feed: UNFloat,
+    kill: UNFloat,
+    d_a: UNFloat,
+    d_b: UNFloat,
+    a: Buffer<UNFloat>,
+    b: Buffer<UNFloat>,
+}
+
+impl TryFrom<GrayScottFields> for GrayScott {
+    type Error = failure::Error;
+
+    fn try_from(fields: GrayScottFields) -> Fallible<Self> {
+        let size = |buffer: &Buffer<UNFloat>| (buffer.width(), buffer.height());
+
+        ensure!(
+            size(&fields.a) == size(&fields.b),
+            "GrayScott buffers differ in size: {:?} and {:?}",
+            size(&fields.a),
+            size(&fields.b)
+        );
+
+        Ok(Self {
+            feed: fields.feed,
+            kill: fields.kill,
+            d_a: fields.d_a,
+            d_b: fields.d_b,
+            a: fields.a,
+            b: fields.b,
+        })
+    }
+}
+
+impl GrayScott {
+    /// Weights for the 3x3 Laplacian kernel, as (adjacent, diagonal). The centre weight is -1.
+    const LAPLACIAN_WEIGHTS: (f32, f32) = (0.2, 0.05);
+
+    /// Starts with the buffers saturated with A and empty of B, so nothing happens until seeded
+    pub fn new(
+        width: usize,
+        height: usize,
+        feed: UNFloat,
+        kill: UNFloat,
+        d_a: UNFloat,
+        d_b: UNFloat,
+    ) -> Self {
+        Self {
+            feed,
+            kill,
+            d_a,
+            d_b,
+            a: Buffer::new(Array2::from_elem((height, width), UNFloat::ONE)),
+            b: Buffer::new(Array2::from_elem((height, width), UNFloat::ZERO)),
+        }
+    }
+
+    pub fn a(&self) -> &Buffer<UNFloat> {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Buffer<UNFloat> {
+        &self.b
+    }
+
+    pub fn width(&self) -> usize {
+        self.a.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.a.height()
+    }
+
+    /// Fills a disc with B, clearing A from it
+    pub fn seed_spot(&mut self, center: SNPoint, radius: UNFloat) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let offset = self.a.cell_center(x, y).into_inner() - center.into_inner();
+
+                if offset.norm() <= radius.into_inner() {
+                    self.a[Point2::new(x, y)] = UNFloat::ZERO;
+                    self.b[Point2::new(x, y)] = UNFloat::ONE;
+                }
+            }
+        }
+    }
+
+    fn laplacian(buffer: &Buffer<UNFloat>, x: usize, y: usize) -> f32 {
+        let (width, height) = (buffer.width(), buffer.height());
+        let (adjacent, diagonal) = Self::LAPLACIAN_WEIGHTS;
+
+        let mut total = -buffer[Point2::new(x, y)].into_inner();
+
+        for dy in [-1, 0, 1] {
+            for dx in [-1, 0, 1] {
+                let weight = match (dx, dy) {
+                    (0, 0) => continue,
+                    (0, _) | (_, 0) => adjacent,
+                    _ => diagonal,
+                };
+
+                let neighbour = Point2::new(
+                    (x as isize + dx).rem_euclid(width as isize) as usize,
+                    (y as isize + dy).rem_euclid(height as isize) as usize,
+                );
+
+                total += weight * buffer[neighbour].into_inner();
+            }
+        }
+
+        total
+    }
+
+    /// Advances the simulation by one unit timestep
+    pub fn step(&mut self) {
+        let (feed, kill) = (self.feed.into_inner(), self.kill.into_inner());
+        let (d_a, d_b) = (self.d_a.into_inner(), self.d_b.into_inner());
+
+        let next = |x: usize, y: usize| {
+            let a = self.a[Point2::new(x, y)].into_inner();
+            let b = self.b[Point2::new(x, y)].into_inner();
+            let reaction = a * b * b;
+
+            (
+                UNFloat::new_clamped(
+                    a + d_a * Self::laplacian(&self.a, x, y) - reaction + feed * (1.0 - a),
+                ),
+                UNFloat::new_clamped(
+                    b + d_b * Self::laplacian(&self.b, x, y) + reaction - (kill + feed) * b,
+                ),
+            )
+        };
+
+        let next: Array2<(UNFloat, UNFloat)> =
+            Array2::from_shape_fn((self.height(), self.width()), |(y, x)| next(x, y));
+
+        self.a = Buffer::new(next.map(|(a, _)| *a));
+        self.b = Buffer::new(next.map(|(_, b)| *b));
+    }
+
+    /// Colors each cell by sampling the ramp at its concentration of B
+    pub fn to_color_buffer(&self, ramp: &ColorRamp) -> Buffer<FloatColor> {
+        Buffer::new(Array2::from_shape_fn(
+            (self.height(), self.width()),
+            |(y, x)| ramp.sample(self.b[Point2::new(x, y)]),
+        ))
+    }
+}
+
+impl<'a> Updatable<'a> for GrayScott {
+    type UpdateArg = ProtoUpdArg<'a>;
+
+    fn update(&mut self, _arg: ProtoUpdArg<'a>) {
+        self.step();
+    }
+}
+
+impl<'a> UpdatableRecursively<'a> for GrayScott {
+    fn update_recursively(&mut self, arg: ProtoUpdArg<'a>) {
+        self.update(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_rejects_mismatched_buffers() {
+        let system = |size| {
+            serde_json::to_value(GrayScott::new(
+                size,
+                size,
+                UNFloat::new(0.055),
+                UNFloat::new(0.062),
+                UNFloat::new(1.0),
+                UNFloat::new(0.5),
+            ))
+            .unwrap()
+        };
+
+        let mut saved = system(4);
+        assert!(serde_json::from_value::<GrayScott>(saved.clone()).is_ok());
+
+        saved["b"] = system(3)["b"].take();
+        assert!(serde_json::from_value::<GrayScott>(saved).is_err());
+    }
+
+    #[test]
+    fn test_seeded_spot_evolves() {
+        let mut system = GrayScott::new(
+            32,
+            32,
+            UNFloat::new(0.055),
+            UNFloat::new(0.062),
+            UNFloat::new(1.0),
+            UNFloat::new(0.5),
+        );
+
+        system.step();
+        assert!(system.b().iter_points().all(|(_, b)| b.into_inner() == 0.0));
+
+        system.seed_spot(SNPoint::zero(), UNFloat::new(0.2));
+        let initial: Vec<f32> = system
+            .b()
+            .iter_points()
+            .map(|(_, b)| b.into_inner())
+            .collect();
+
+        for _ in 0..200 {
+            system.step();
+        }
+
+        let evolved: Vec<f32> = system
+            .b()
+            .iter_points()
+            .map(|(_, b)| b.into_inner())
+            .collect();
+
+        let changed = initial
+            .iter()
+            .zip(evolved.iter())
+            .filter(|(a, b)| (*a - *b).abs() > 0.1)
+            .count();
+
+        assert!(changed > 10);
+        assert!(evolved.iter().any(|&b| b > 0.1));
+
+        let colors = system.to_color_buffer(&ColorRamp::new(
+            vec![
+                ColorStop {
+                    position: UNFloat::ZERO,
+                    color: FloatColor::BLACK,
+                },
+                ColorStop {
+                    position: UNFloat::ONE,
+                    color: FloatColor::WHITE,
+                },
+            ],
+            ColorRampInterpolation::Linear,
+        ));
+
+        assert_eq!((colors.width(), colors.height()), (32, 32));
+    }
+}
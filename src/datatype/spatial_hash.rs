@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use float_ord::FloatOrd;
+use nalgebra::*;
+
+use crate::prelude::*;
+
+/// Buckets point indices into square cells for nearest and radius queries.
+/// The points themselves are not stored, so queries take the slice the hash was built from.
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    grid: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn build(points: &[SNPoint], cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "Invalid SpatialHash cell size: {}",
+            cell_size
+        );
+
+        let mut grid: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+
+        for (i, p) in points.iter().enumerate() {
+            grid.entry(cell(p.into_inner(), cell_size))
+                .or_default()
+                .push(i);
+        }
+
+        Self { cell_size, grid }
+    }
+
+    /// Index of the closest point to p, preferring the lowest index on ties.
+    /// Panics if points is empty.
+    pub fn nearest(&self, p: SNPoint, points: &[SNPoint], f: DistanceFunction) -> usize {
+        let candidate = |i: usize| {
+            (
+                FloatOrd(f.calculate_point2(points[i].into_inner(), p.into_inner())),
+                i,
+            )
+        };
+
+        let scale = match chebyshev_scale(f) {
+            Some(scale) => scale,
+            None => return (0..points.len()).min_by_key(|&i| candidate(i)).unwrap(),
+        };
+
+        let (cx, cy) = cell(p.into_inner(), self.cell_size);
+        let mut best: Option<(FloatOrd<f32>, usize)> = None;
+
+        for ring in 0..=self.max_ring() {
+            for key in ring_cells(cx, cy, ring) {
+                for &i in self.grid.get(&key).into_iter().flatten() {
+                    let c = candidate(i);
+
+                    best = Some(best.map_or(c, |b| b.min(c)));
+                }
+            }
+
+            // Anything in a further ring is at least ring cells away on some axis
+            if let Some((FloatOrd(d), _)) = best {
+                if d < scale * ring as f32 * self.cell_size {
+                    break;
+                }
+            }
+        }
+
+        best.expect("Cannot find the nearest point of an empty set")
+            .1
+    }
+
+    /// Indices of every point within radius of p, in ascending order
+    pub fn within(
+        &self,
+        p: SNPoint,
+        radius: f32,
+        points: &[SNPoint],
+        f: DistanceFunction,
+    ) -> Vec<usize> {
+        let in_range =
+            |i: &usize| f.calculate_point2(points[*i].into_inner(), p.into_inner()) <= radius;
+
+        let scale = match chebyshev_scale(f) {
+            Some(scale) => scale,
+            None => return (0..points.len()).filter(in_range).collect(),
+        };
+
+        let (cx, cy) = cell(p.into_inner(), self.cell_size);
+        let reach = ((radius / scale / self.cell_size).ceil() as i32).min(self.max_ring());
+
+        let mut found: Vec<usize> = (-reach..=reach)
+            .flat_map(|dy| (-reach..=reach).map(move |dx| (cx + dx, cy + dy)))
+            .flat_map(|key| self.grid.get(&key).into_iter().flatten().copied())
+            .filter(in_range)
+            .collect();
+
+        found.sort_unstable();
+        found
+    }
+
+    /// The furthest ring that can hold a point in [-1, 1]
+    fn max_ring(&self) -> i32 {
+        (2.0 / self.cell_size).ceil() as i32 + 1
+    }
+}
+
+fn cell(p: Point2<f32>, cell_size: f32) -> (i32, i32) {
+    (
+        (p.x / cell_size).floor() as i32,
+        (p.y / cell_size).floor() as i32,
+    )
+}
+
+/// Cells whose Chebyshev distance from (cx, cy) is exactly ring
+fn ring_cells(cx: i32, cy: i32, ring: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-ring..=ring).flat_map(move |dy| {
+        // Rows strictly inside the ring only touch it at their two ends
+        let step = if dy.abs() == ring {
+            1
+        } else {
+            2 * ring as usize
+        };
+
+        (-ring..=ring)
+            .step_by(step)
+            .map(move |dx| (cx + dx, cy + dy))
+    })
+}
+
+/// A factor k such that f(a, b) >= k * chebyshev(a, b), or None if f has no such bound
+fn chebyshev_scale(f: DistanceFunction) -> Option<f32> {
+    match f {
+        // Minkowski is at least 2^(-1/p) times Chebyshev, and p is at least 1
+        DistanceFunction::Euclidean
+        | DistanceFunction::Manhattan
+        | DistanceFunction::Minkowski { .. } => Some(0.5),
+        DistanceFunction::Chebyshev => Some(1.0),
+        DistanceFunction::Minimum => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    const FUNCTIONS: [DistanceFunction; 6] = [
+        DistanceFunction::Euclidean,
+        DistanceFunction::Manhattan,
+        DistanceFunction::Chebyshev,
+        DistanceFunction::Minimum,
+        DistanceFunction::Minkowski { p: UNFloat::ZERO },
+        DistanceFunction::Minkowski { p: UNFloat::ONE },
+    ];
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for &cell_size in &[0.05, 0.3, 3.0] {
+            let points: Vec<SNPoint> = (0..100).map(|_| SNPoint::random(&mut rng)).collect();
+            let hash = SpatialHash::build(&points, cell_size);
+
+            for _ in 0..100 {
+                let p = SNPoint::random(&mut rng);
+
+                for &f in &FUNCTIONS {
+                    let expected = (0..points.len())
+                        .min_by_key(|&i| {
+                            FloatOrd(f.calculate_point2(points[i].into_inner(), p.into_inner()))
+                        })
+                        .unwrap();
+
+                    assert_eq!(hash.nearest(p, &points, f), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_within_matches_brute_force() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        for &cell_size in &[0.05, 0.3, 3.0] {
+            let points: Vec<SNPoint> = (0..100).map(|_| SNPoint::random(&mut rng)).collect();
+            let hash = SpatialHash::build(&points, cell_size);
+
+            for _ in 0..100 {
+                let p = SNPoint::random(&mut rng);
+                let radius = rng.gen_range(0.0..0.5);
+
+                for &f in &FUNCTIONS {
+                    let expected: Vec<usize> = (0..points.len())
+                        .filter(|&i| {
+                            f.calculate_point2(points[i].into_inner(), p.into_inner()) <= radius
+                        })
+                        .collect();
+
+                    assert_eq!(hash.within(p, radius, &points, f), expected);
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@ pub mod datatype;
 pub mod mutagen_args;
 pub mod prelude;
 pub mod profiler;
+pub mod soak;
+pub mod traits;
 pub mod util;
 
 pub use nalgebra;
\ No newline at end of file
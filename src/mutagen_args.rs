@@ -43,12 +43,55 @@ impl<'a> mutagen::State for ProtoGenArg<'a> {
 
 pub struct ProtoMutArg<'a> {
     pub profiler: &'a mut Option<MutagenProfiler>,
+    /// How many reborrows separate this arg from the root of the mutation
+    pub depth: usize,
+    /// Overall mutation aggressiveness, defaulting to DEFAULT_INTENSITY when unset
+    pub intensity: Option<UNFloat>,
+}
+
+impl<'a> ProtoMutArg<'a> {
+    pub const DEFAULT_INTENSITY: f32 = 0.5;
+
+    pub fn new(profiler: &'a mut Option<MutagenProfiler>) -> Self {
+        Self {
+            profiler,
+            depth: 0,
+            intensity: None,
+        }
+    }
+
+    pub fn with_intensity(profiler: &'a mut Option<MutagenProfiler>, intensity: UNFloat) -> Self {
+        Self {
+            profiler,
+            depth: 0,
+            intensity: Some(intensity),
+        }
+    }
+
+    /// The intensity at the root, rising towards double the intensity in deeper nodes,
+    /// so shallow nodes mutate more gently than leaves
+    pub fn magnitude(&self) -> UNFloat {
+        let intensity = self
+            .intensity
+            .map_or(Self::DEFAULT_INTENSITY, UNFloat::into_inner);
+        let depth_scale = 2.0 * (self.depth + 1) as f32 / (self.depth + 2) as f32;
+
+        UNFloat::new_clamped(intensity * depth_scale)
+    }
+
+    /// Chance of stopping at each step of a local mutation, 0.5 at the root by default.
+    /// Falls as magnitude rises, so the mean step count is 1 + 2 * magnitude.
+    pub fn decay(&self) -> UNFloat {
+        UNFloat::new(1.0 / (1.0 + 2.0 * self.magnitude().into_inner()))
+    }
 }
 
 impl<'a, 'b: 'a> Reborrow<'a, 'b, ProtoMutArg<'a>> for ProtoMutArg<'b> {
     fn reborrow(&'a mut self) -> ProtoMutArg<'a> {
         ProtoMutArg {
             profiler: &mut self.profiler,
+            depth: self.depth + 1,
+            intensity: self.intensity,
         }
     }
 }
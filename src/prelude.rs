@@ -1,10 +1,12 @@
 pub use crate::{
     datatype::{
-        automata_rules::*, buffers::*, color_blend_functions::*, colors::*, complex::*,
-        constraint_resolvers::*, continuous::*, discrete::*, distance_functions::*,
-        iterative_results::*, matrices::*, noisefunctions::*, point_sets::*, points::*,
+        automata_rules::*, buffers::*, color_blend_functions::*, color_ramps::*, colors::*,
+        complex::*, constraint_resolvers::*, continuous::*, discrete::*, distance_functions::*,
+        iterative_results::*, matrices::*, noisefunctions::*, oscillators::*, point_sets::*,
+        points::*, reaction_diffusion::*, spatial_hash::*,
     },
     mutagen_args::*,
     profiler::*,
+    traits::{crossover::*, ranged::*},
     util::*,
 };
@@ -0,0 +1,349 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    panic::{self, AssertUnwindSafe},
+};
+
+use failure::Fallible;
+use mutagen::{Generatable, Mutatable, Updatable, UpdatableRecursively};
+use rand::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::prelude::*;
+
+/// Stress tests generation, mutation, updating and serialization of every major datatype.
+/// Each cycle is seeded from the master seed, so any failure can be replayed from its report.
+pub fn soak(seed: u128, cycles: usize) -> Result<(), SoakFailure> {
+    let mut rng = DeterministicRng::from_seed(seed.to_le_bytes());
+
+    for cycle in 0..cycles {
+        let mut soak_cycle = SoakCycle {
+            rng: &mut rng,
+            seed,
+            cycle,
+        };
+
+        soak_cycle.run::<PointSet>("PointSet")?;
+        soak_cycle.run::<WeightedPointSet>("WeightedPointSet")?;
+        soak_cycle.run::<ElementaryAutomataRule>("ElementaryAutomataRule")?;
+        soak_cycle.run::<NeighbourCountAutomataRule>("NeighbourCountAutomataRule")?;
+        soak_cycle.run::<IndivAutomataRule>("IndivAutomataRule")?;
+        soak_cycle.run::<LifeLikeAutomataRule>("LifeLikeAutomataRule")?;
+        soak_cycle.run::<NoiseFunctions>("NoiseFunctions")?;
+        soak_cycle.run::<BitColor>("BitColor")?;
+        soak_cycle.run::<NibbleColor>("NibbleColor")?;
+        soak_cycle.run::<ByteColor>("ByteColor")?;
+        soak_cycle.run::<FloatColor>("FloatColor")?;
+        soak_cycle.run::<HSVColor>("HSVColor")?;
+        soak_cycle.run::<CMYKColor>("CMYKColor")?;
+        soak_cycle.run::<LABColor>("LABColor")?;
+        soak_cycle.run::<LChColor>("LChColor")?;
+        soak_cycle.run::<ColorRamp>("ColorRamp")?;
+        soak_cycle.run::<Buffer<FloatColor>>("Buffer<FloatColor>")?;
+    }
+
+    Ok(())
+}
+
+pub struct SoakCycle<'a> {
+    pub rng: &'a mut DeterministicRng,
+    pub seed: u128,
+    pub cycle: usize,
+}
+
+impl<'a> SoakCycle<'a> {
+    /// Generates a T and applies a random sequence of operations to it, shrinking the sequence on failure
+    pub fn run<T>(&mut self, datatype: &'static str) -> Result<(), SoakFailure>
+    where
+        T: for<'b> Generatable<'b, GenArg = ProtoGenArg<'b>>
+            + for<'b> Mutatable<'b, MutArg = ProtoMutArg<'b>>
+            + for<'b> Updatable<'b, UpdateArg = ProtoUpdArg<'b>>
+            + for<'b> UpdatableRecursively<'b>
+            + Serialize
+            + DeserializeOwned
+            + SoakValidate,
+    {
+        let trace_seed = self.rng.gen();
+        let op_count = self.rng.gen_range(1..=MAX_OPS);
+        let trace: Vec<SoakOp> = (0..op_count).map(|_| SoakOp::random(self.rng)).collect();
+
+        let message = match run_trace::<T>(trace_seed, &trace) {
+            Ok(()) => return Ok(()),
+            Err(message) => message,
+        };
+
+        let (trace, message) = shrink_trace::<T>(trace_seed, trace, message);
+
+        Err(SoakFailure {
+            seed: self.seed,
+            cycle: self.cycle,
+            trace_seed,
+            datatype,
+            trace,
+            message,
+        })
+    }
+}
+
+const MAX_OPS: usize = 16;
+
+/// Checks a soaked value after every operation, through the validation its constructors use
+pub trait SoakValidate {
+    fn soak_validate(&self) -> Fallible<()> {
+        Ok(())
+    }
+}
+
+impl SoakValidate for PointSet {
+    fn soak_validate(&self) -> Fallible<()> {
+        self.validate()
+    }
+}
+
+impl SoakValidate for WeightedPointSet {
+    fn soak_validate(&self) -> Fallible<()> {
+        self.validate()
+    }
+}
+
+impl SoakValidate for ColorRamp {
+    fn soak_validate(&self) -> Fallible<()> {
+        self.validate()
+    }
+}
+
+impl SoakValidate for ElementaryAutomataRule {}
+impl SoakValidate for NeighbourCountAutomataRule {}
+impl SoakValidate for IndivAutomataRule {}
+impl SoakValidate for LifeLikeAutomataRule {}
+impl SoakValidate for NoiseFunctions {}
+impl SoakValidate for BitColor {}
+impl SoakValidate for NibbleColor {}
+impl SoakValidate for ByteColor {}
+impl SoakValidate for FloatColor {}
+impl SoakValidate for HSVColor {}
+impl SoakValidate for CMYKColor {}
+impl SoakValidate for LABColor {}
+impl SoakValidate for LChColor {}
+impl<T> SoakValidate for Buffer<T> {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoakOp {
+    Mutate,
+    Update,
+    UpdateRecursively,
+    RoundTrip,
+}
+
+impl SoakOp {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        match rng.gen_range(0..4) {
+            0 => SoakOp::Mutate,
+            1 => SoakOp::Update,
+            2 => SoakOp::UpdateRecursively,
+            3 => SoakOp::RoundTrip,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SoakFailure {
+    pub seed: u128,
+    pub cycle: usize,
+    pub trace_seed: u64,
+    pub datatype: &'static str,
+    pub trace: Vec<SoakOp>,
+    pub message: String,
+}
+
+impl Display for SoakFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Soak failure for {} (seed {}, cycle {}, trace seed {}): {}\nTrace: Generate",
+            self.datatype, self.seed, self.cycle, self.trace_seed, self.message
+        )?;
+
+        for op in self.trace.iter() {
+            write!(f, " -> {:?}", op)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replays a single trace from its seed, converting panics and broken invariants into errors
+pub fn run_trace<T>(trace_seed: u64, trace: &[SoakOp]) -> Result<(), String>
+where
+    T: for<'b> Generatable<'b, GenArg = ProtoGenArg<'b>>
+        + for<'b> Mutatable<'b, MutArg = ProtoMutArg<'b>>
+        + for<'b> Updatable<'b, UpdateArg = ProtoUpdArg<'b>>
+        + for<'b> UpdatableRecursively<'b>
+        + Serialize
+        + DeserializeOwned
+        + SoakValidate,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut rng = DeterministicRng::seed_from_u64(trace_seed);
+        let mut profiler = None;
+
+        let mut value = T::generate_rng(
+            &mut rng,
+            ProtoGenArg {
+                profiler: &mut profiler,
+            },
+        );
+
+        value.soak_validate().map_err(|e| e.to_string())?;
+
+        for op in trace {
+            match op {
+                SoakOp::Mutate => value.mutate_rng(&mut rng, ProtoMutArg::new(&mut profiler)),
+                SoakOp::Update => value.update(ProtoUpdArg {
+                    profiler: &mut profiler,
+                }),
+                SoakOp::UpdateRecursively => value.update_recursively(ProtoUpdArg {
+                    profiler: &mut profiler,
+                }),
+                SoakOp::RoundTrip => {
+                    let serialized = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+                    value = serde_json::from_str(&serialized).map_err(|e| e.to_string())?;
+                    let reserialized = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+
+                    if serialized != reserialized {
+                        return Err(format!(
+                            "Serialization is not stable: {} became {}",
+                            serialized, reserialized
+                        ));
+                    }
+                }
+            }
+
+            value
+                .soak_validate()
+                .map_err(|e| format!("Invalid after {:?}: {}", op, e))?;
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(result) => result,
+        Err(payload) => Err(if let Some(s) = payload.downcast_ref::<&str>() {
+            format!("Panicked: {}", s)
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            format!("Panicked: {}", s)
+        } else {
+            "Panicked".to_string()
+        }),
+    }
+}
+
+/// Greedily drops operations from a failing trace while it keeps failing
+fn shrink_trace<T>(
+    trace_seed: u64,
+    mut trace: Vec<SoakOp>,
+    mut message: String,
+) -> (Vec<SoakOp>, String)
+where
+    T: for<'b> Generatable<'b, GenArg = ProtoGenArg<'b>>
+        + for<'b> Mutatable<'b, MutArg = ProtoMutArg<'b>>
+        + for<'b> Updatable<'b, UpdateArg = ProtoUpdArg<'b>>
+        + for<'b> UpdatableRecursively<'b>
+        + Serialize
+        + DeserializeOwned
+        + SoakValidate,
+{
+    let mut i = trace.len();
+
+    while i > 0 {
+        i -= 1;
+
+        let mut candidate = trace.clone();
+        candidate.remove(i);
+
+        if let Err(candidate_message) = run_trace::<T>(trace_seed, &candidate) {
+            trace = candidate;
+            message = candidate_message;
+            i = i.min(trace.len());
+        }
+    }
+
+    (trace, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Fragile {
+        mutations: u8,
+    }
+
+    impl<'a> Generatable<'a> for Fragile {
+        type GenArg = ProtoGenArg<'a>;
+
+        fn generate_rng<R: Rng + ?Sized>(_rng: &mut R, _arg: ProtoGenArg<'a>) -> Self {
+            Self { mutations: 0 }
+        }
+    }
+
+    impl<'a> Mutatable<'a> for Fragile {
+        type MutArg = ProtoMutArg<'a>;
+
+        fn mutate_rng<R: Rng + ?Sized>(&mut self, _rng: &mut R, _arg: ProtoMutArg<'a>) {
+            self.mutations += 1;
+            assert!(self.mutations < 2, "Mutated twice");
+        }
+    }
+
+    impl<'a> Updatable<'a> for Fragile {
+        type UpdateArg = ProtoUpdArg<'a>;
+
+        fn update(&mut self, _arg: ProtoUpdArg<'a>) {}
+    }
+
+    impl<'a> UpdatableRecursively<'a> for Fragile {
+        fn update_recursively(&mut self, _arg: ProtoUpdArg<'a>) {}
+    }
+
+    impl SoakValidate for Fragile {}
+
+    #[test]
+    fn test_soak_short() {
+        if let Err(failure) = soak(0, 10) {
+            panic!("{}", failure);
+        }
+    }
+
+    #[test]
+    fn test_soak_shrinks_failing_trace() {
+        let mut rng = DeterministicRng::seed_from_u64(0);
+
+        let failure = (0..1000)
+            .find_map(|cycle| {
+                SoakCycle {
+                    rng: &mut rng,
+                    seed: 0,
+                    cycle,
+                }
+                .run::<Fragile>("Fragile")
+                .err()
+            })
+            .unwrap();
+
+        assert_eq!(failure.trace, vec![SoakOp::Mutate, SoakOp::Mutate]);
+        assert!(failure.message.contains("Mutated twice"));
+    }
+
+    #[test]
+    #[ignore]
+    fn soak_long() {
+        if let Err(failure) = soak(0, 100_000) {
+            panic!("{}", failure);
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod crossover;
+pub mod ranged;
@@ -0,0 +1,6 @@
+use rand::prelude::*;
+
+/// Genetic recombination of two parents into a single offspring
+pub trait Crossover {
+    fn crossover<R: Rng + ?Sized>(a: &Self, b: &Self, rng: &mut R) -> Self;
+}
@@ -1,4 +1,47 @@
-pub trait Ranged {
+use crate::datatype::continuous::UNFloat;
+
+/// A datatype with a fixed, ordered range of values
+pub trait Ranged: Sized + Copy {
     fn max_value() -> Self;
     fn min_value() -> Self;
-}
\ No newline at end of file
+
+    /// The value as a float on the same scale as min_value and max_value
+    fn to_f64(self) -> f64;
+
+    /// Where the value sits between min_value and max_value, for converting discrete values
+    /// to continuous ones
+    fn to_unfloat(self) -> UNFloat {
+        let min = Self::min_value().to_f64();
+        let max = Self::max_value().to_f64();
+
+        UNFloat::new_clamped(((self.to_f64() - min) / (max - min)) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn assert_range_ends<T: Ranged>() {
+        assert_eq!(T::min_value().to_unfloat(), UNFloat::ZERO);
+        assert_eq!(T::max_value().to_unfloat(), UNFloat::ONE);
+    }
+
+    #[test]
+    fn test_range_ends() {
+        assert_range_ends::<Boolean>();
+        assert_range_ends::<Nibble>();
+        assert_range_ends::<Byte>();
+        assert_range_ends::<UInt>();
+        assert_range_ends::<SInt>();
+        assert_range_ends::<UNFloat>();
+        assert_range_ends::<SNFloat>();
+        assert_range_ends::<Angle>();
+
+        assert_eq!(Nibble::new(5).to_unfloat(), UNFloat::new(1.0 / 3.0));
+        assert!(SInt::new(0).to_unfloat().approx_eq(UNFloat::new(0.5), 1e-6));
+        assert!(Angle::ZERO.to_unfloat().approx_eq(UNFloat::new(0.5), 1e-6));
+        assert_eq!(SNFloat::new(0.5).to_unfloat(), UNFloat::new(0.75));
+    }
+}
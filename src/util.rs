@@ -9,9 +9,11 @@ use lazy_static::lazy_static;
 use lerp::Lerp;
 use log::debug;
 use nalgebra::*;
-use rand::{RngCore, SeedableRng};
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
 use walkdir::WalkDir;
 
+use crate::datatype::continuous::UNFloat;
+
 pub fn collect_filenames<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
     let mut vec: Vec<_> = WalkDir::new(path)
         .into_iter()
@@ -213,6 +215,29 @@ where
 //     image
 // }
 
+/// Thomas Wang's 32 bit integer hash
+pub fn wang_hash(mut key: u32) -> u32 {
+    key = (key ^ 61) ^ (key >> 16);
+    key = key.wrapping_mul(9);
+    key ^= key >> 4;
+    key = key.wrapping_mul(0x27d4_eb2d);
+    key ^ (key >> 15)
+}
+
+/// A stable pseudo-random value for each pixel and seed, for effects that need randomness keyed
+/// to position rather than drawn in sequence
+pub fn pixel_hash(x: usize, y: usize, seed: u32) -> UNFloat {
+    let hash = wang_hash(seed ^ wang_hash(x as u32 ^ wang_hash(y as u32).rotate_left(16)));
+
+    UNFloat::new((f64::from(hash) / f64::from(u32::MAX)) as f32)
+}
+
+/// Shuffles a slice using only the given rng, so that seeded generation produces the same order
+/// every time. Prefer this over reaching for a thread-local rng when shuffling genome data.
+pub fn seeded_shuffle<T, R: Rng + ?Sized>(slice: &mut [T], rng: &mut R) {
+    slice.shuffle(rng);
+}
+
 pub fn lerp<F, T: Lerp<F>>(a: T, b: T, value: F) -> T {
     T::lerp(a, b, value)
 }
@@ -224,3 +249,28 @@ pub fn local_path<P: AsRef<Path>>(filename: P) -> PathBuf {
         PathBuf::from(env::current_dir().expect("Unable to get current dir")).join(filename)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_pixel_hash() {
+        assert_eq!(pixel_hash(12, 34, 56), pixel_hash(12, 34, 56));
+        assert_ne!(pixel_hash(12, 34, 56), pixel_hash(34, 12, 56));
+        assert_ne!(pixel_hash(12, 34, 56), pixel_hash(12, 34, 57));
+
+        let values: HashSet<u32> = (0..64)
+            .flat_map(|y| (0..64).map(move |x| pixel_hash(x, y, 7).into_inner().to_bits()))
+            .collect();
+        assert!(values.len() > 4000);
+
+        let mean = (0..64)
+            .flat_map(|y| (0..64).map(move |x| pixel_hash(x, y, 7).into_inner()))
+            .sum::<f32>()
+            / 4096.0;
+        assert!((mean - 0.5).abs() < 0.02);
+    }
+}